@@ -0,0 +1,353 @@
+use crate::net;
+use crate::openai_compat::{self, ProviderConfig};
+use crate::usage::{self, UsageRecord};
+use crate::whisper::{LoadedModel, TranscriptionOutput, WhisperModel, WhisperRuntimeConfig};
+use crate::AppSettings;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tauri_specta::Event;
+
+pub const LOCAL_BACKEND_ID: &str = "local";
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct BackendCapabilities {
+    pub streaming: bool,
+    pub timestamps: bool,
+    pub translation: bool,
+    pub languages: Vec<String>, // Empty means any language Whisper supports
+    pub on_device: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct BackendInfo {
+    pub id: String,
+    pub name: String,
+    pub capabilities: BackendCapabilities,
+    pub available: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type, Event)]
+pub struct BackendFallback {
+    pub from_backend: String,
+    pub to_backend: String,
+    pub reason: String,
+}
+
+pub struct BackendOutput {
+    pub output: TranscriptionOutput,
+    pub model_used: Option<String>,
+    /// Set when the text is a draft that should be re-decoded by this model.
+    pub refinement: Option<LoadedModel>,
+}
+
+#[async_trait]
+pub trait TranscriptionBackend: Send + Sync {
+    fn id(&self) -> String;
+    fn name(&self) -> String;
+    fn capabilities(&self, app: &AppHandle) -> BackendCapabilities;
+    fn is_available(&self, app: &AppHandle) -> bool;
+    async fn transcribe(
+        &self,
+        app: &AppHandle,
+        audio_data: &[f32],
+        sample_rate: u32,
+        config: &WhisperRuntimeConfig,
+    ) -> Result<BackendOutput, String>;
+
+    fn info(&self, app: &AppHandle) -> BackendInfo {
+        BackendInfo {
+            id: self.id(),
+            name: self.name(),
+            capabilities: self.capabilities(app),
+            available: self.is_available(app),
+        }
+    }
+}
+
+pub struct LocalWhisperBackend;
+
+#[async_trait]
+impl TranscriptionBackend for LocalWhisperBackend {
+    fn id(&self) -> String {
+        LOCAL_BACKEND_ID.to_string()
+    }
+
+    fn name(&self) -> String {
+        "Local Whisper".to_string()
+    }
+
+    fn capabilities(&self, app: &AppHandle) -> BackendCapabilities {
+        let english_only = AppSettings::get_or_default(app)
+            .selected_model
+            .map(|id| id.contains(".en"))
+            .unwrap_or(false);
+        BackendCapabilities {
+            streaming: false,
+            timestamps: true,
+            translation: !english_only,
+            languages: if english_only { vec!["en".to_string()] } else { Vec::new() },
+            on_device: true,
+        }
+    }
+
+    fn is_available(&self, app: &AppHandle) -> bool {
+        app.state::<Arc<Mutex<WhisperModel>>>()
+            .lock()
+            .unwrap()
+            .primary()
+            .is_some()
+    }
+
+    async fn transcribe(
+        &self,
+        app: &AppHandle,
+        audio_data: &[f32],
+        sample_rate: u32,
+        config: &WhisperRuntimeConfig,
+    ) -> Result<BackendOutput, String> {
+        let settings = AppSettings::get_or_default(app);
+        let whisper_state = app.state::<Arc<Mutex<WhisperModel>>>();
+
+        let (draft, accurate) = {
+            let mut model = whisper_state.lock().unwrap();
+            let profile_model = config
+                .local_model
+                .as_deref()
+                .and_then(|id| match model.load_profile_model(id) {
+                    Ok(loaded) => Some(loaded),
+                    Err(e) => {
+                        eprintln!("Can't use profile model {}, using the selected one: {}", id, e);
+                        None
+                    }
+                });
+            let accurate = profile_model
+                .or_else(|| model.primary())
+                .ok_or_else(|| "Model not loaded".to_string())?;
+            let draft = if settings.two_pass_enabled { model.draft() } else { None };
+            (draft, accurate)
+        };
+
+        match draft {
+            Some(draft) if draft.model_id != accurate.model_id => {
+                println!("✏️ Drafting with {} before refining with {}", draft.model_id, accurate.model_id);
+                let output = draft.transcribe_detailed(audio_data, sample_rate, config)?;
+                Ok(BackendOutput {
+                    output,
+                    model_used: Some(accurate.model_id.clone()),
+                    refinement: Some(accurate),
+                })
+            }
+            _ => {
+                let output = accurate.transcribe_detailed(audio_data, sample_rate, config)?;
+                Ok(BackendOutput {
+                    output,
+                    model_used: Some(accurate.model_id),
+                    refinement: None,
+                })
+            }
+        }
+    }
+}
+
+/// Any server speaking the OpenAI `/audio/transcriptions` API: Groq, OpenAI or
+/// a self-hosted Whisper server. The provider ID doubles as the backend ID.
+pub struct OpenAiCompatibleBackend {
+    provider: ProviderConfig,
+}
+
+impl OpenAiCompatibleBackend {
+    pub fn new(provider: ProviderConfig) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl TranscriptionBackend for OpenAiCompatibleBackend {
+    fn id(&self) -> String {
+        self.provider.id.clone()
+    }
+
+    fn name(&self) -> String {
+        self.provider.name.clone()
+    }
+
+    fn capabilities(&self, _app: &AppHandle) -> BackendCapabilities {
+        BackendCapabilities {
+            streaming: false,
+            timestamps: true,
+            translation: false,
+            languages: Vec::new(),
+            on_device: false,
+        }
+    }
+
+    fn is_available(&self, app: &AppHandle) -> bool {
+        if AppSettings::get_or_default(app).privacy_mode {
+            return false;
+        }
+        !self.provider.requires_api_key()
+            || openai_compat::has_api_key(app, &self.provider.id)
+    }
+
+    async fn transcribe(
+        &self,
+        app: &AppHandle,
+        audio_data: &[f32],
+        sample_rate: u32,
+        config: &WhisperRuntimeConfig,
+    ) -> Result<BackendOutput, String> {
+        net::check_egress(app, "cloud transcription", &self.provider.transcriptions_url())?;
+        usage::check_hard_limits(app)?;
+
+        let mut provider = self.provider.clone();
+        if let Some(model) = &config.cloud_model {
+            provider.model = model.clone();
+        }
+
+        let key = openai_compat::get_api_key(app, &provider.id);
+        let request_start = std::time::Instant::now();
+        let result = openai_compat::transcribe(
+            &provider,
+            &AppSettings::get_or_default(app).network,
+            key.as_deref(),
+            audio_data,
+            sample_rate,
+            config.language.clone(),
+            config.initial_prompt.clone(),
+        )
+        .await
+        .map_err(|e| format!("{}: {}", provider.name, e))?;
+
+        let audio_secs = audio_data.len() as f64 / sample_rate as f64;
+        usage::record(
+            app,
+            UsageRecord {
+                timestamp: chrono::Utc::now().timestamp_millis() as f64,
+                provider_id: provider.id.clone(),
+                model: provider.model.clone(),
+                audio_secs,
+                bytes_uploaded: result.bytes_uploaded as f64,
+                latency_ms: request_start.elapsed().as_secs_f64() * 1000.0,
+                estimated_cost_usd: provider.cost_per_hour_usd.map(|rate| rate * audio_secs / 3600.0),
+            },
+        );
+
+        Ok(BackendOutput {
+            output: result.output,
+            model_used: Some(format!("{}/{}", provider.id, provider.model)),
+            refinement: None,
+        })
+    }
+}
+
+/// Local Whisper plus one backend per configured cloud provider. Providers are
+/// read from settings on each lookup so edits apply without a restart.
+pub struct BackendRegistry {
+    builtin: Vec<Arc<dyn TranscriptionBackend>>,
+}
+
+impl Default for BackendRegistry {
+    fn default() -> Self {
+        Self {
+            builtin: vec![Arc::new(LocalWhisperBackend)],
+        }
+    }
+}
+
+impl BackendRegistry {
+    fn backends(&self, app: &AppHandle) -> Vec<Arc<dyn TranscriptionBackend>> {
+        let mut backends = self.builtin.clone();
+        backends.extend(
+            openai_compat::providers(app)
+                .into_iter()
+                .map(|p| Arc::new(OpenAiCompatibleBackend::new(p)) as Arc<dyn TranscriptionBackend>),
+        );
+        backends
+    }
+
+    pub fn get(&self, app: &AppHandle, id: &str) -> Option<Arc<dyn TranscriptionBackend>> {
+        self.backends(app).into_iter().find(|b| b.id() == id)
+    }
+
+    pub fn list(&self, app: &AppHandle) -> Vec<BackendInfo> {
+        self.backends(app).iter().map(|b| b.info(app)).collect()
+    }
+
+    /// The backend picked in settings, falling back to local Whisper for unknown IDs.
+    pub fn active(&self, app: &AppHandle) -> Arc<dyn TranscriptionBackend> {
+        self.resolve(app, None)
+    }
+
+    /// `preferred` if it's registered, such as the backend a dictation profile
+    /// names, otherwise the active backend.
+    pub fn resolve(&self, app: &AppHandle, preferred: Option<&str>) -> Arc<dyn TranscriptionBackend> {
+        if let Some(backend) = preferred.and_then(|id| self.get(app, id)) {
+            return backend;
+        }
+        let id = AppSettings::get_or_default(app)
+            .active_backend
+            .unwrap_or_else(|| LOCAL_BACKEND_ID.to_string());
+        self.get(app, &id)
+            .or_else(|| self.get(app, LOCAL_BACKEND_ID))
+            .expect("local backend is always registered")
+    }
+}
+
+/// Transcribes with `backend`, and if that is a cloud backend that fails,
+/// retries the same audio with local Whisper when a model is loaded so the
+/// dictation isn't lost.
+pub async fn transcribe_with_fallback(
+    app: &AppHandle,
+    backend: Arc<dyn TranscriptionBackend>,
+    audio_data: &[f32],
+    sample_rate: u32,
+    config: &WhisperRuntimeConfig,
+) -> Result<BackendOutput, String> {
+    let err = match backend.transcribe(app, audio_data, sample_rate, config).await {
+        Ok(output) => return Ok(output),
+        Err(err) => err,
+    };
+
+    let local = LocalWhisperBackend;
+    if backend.id() == local.id() || !local.is_available(app) {
+        return Err(err);
+    }
+
+    println!("⚠️ {} failed ({}), falling back to local Whisper", backend.name(), err);
+    let _ = app
+        .notification()
+        .builder()
+        .title("Transcribed locally")
+        .body(format!("{} was unavailable, so local Whisper was used instead.\n{}", backend.name(), err))
+        .show();
+    BackendFallback {
+        from_backend: backend.id(),
+        to_backend: local.id(),
+        reason: err,
+    }
+    .emit(app)
+    .ok();
+
+    local.transcribe(app, audio_data, sample_rate, config).await
+}
+
+/// Before backends were selectable, having a Groq key stored meant "use Groq".
+/// Persist that choice explicitly the first time we start with this setting.
+pub fn migrate_active_backend(app: &AppHandle) {
+    let mut settings = AppSettings::get_or_default(app);
+    if settings.active_backend.is_some() {
+        return;
+    }
+
+    let id = if openai_compat::has_api_key(app, openai_compat::GROQ_PROVIDER_ID) {
+        openai_compat::GROQ_PROVIDER_ID
+    } else {
+        LOCAL_BACKEND_ID
+    };
+    println!("🔀 Setting active transcription backend to {}", id);
+    settings.active_backend = Some(id.to_string());
+    let _ = AppSettings::set(app, &settings);
+}
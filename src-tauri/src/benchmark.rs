@@ -0,0 +1,285 @@
+use crate::audio::read_wav_mono;
+use crate::metrics;
+use crate::whisper::{WhisperModel, WhisperRuntimeConfig};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
+
+const REFERENCE_CLIP: &str = "reference.wav";
+const REFERENCE_TEXT: &str =
+    "And so my fellow Americans, ask not what your country can do for you, ask what you can do for your country.";
+
+// A model only counts as a recommendation candidate if it keeps up with speech comfortably.
+const MAX_RECOMMENDED_RTF: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type, Event)]
+pub struct BenchmarkProgress {
+    pub model_id: String,
+    pub completed: u32,
+    pub total: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ModelBenchmark {
+    pub model_id: String,
+    pub load_time_ms: f64,
+    pub transcribe_time_ms: f64,
+    pub real_time_factor: f32,
+    pub peak_memory_mb: f64,
+    pub word_error_rate: f32,
+    pub transcript: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
+pub struct BenchmarkReport {
+    pub run_at: f64, // Unix timestamp in milliseconds
+    pub clip_duration_secs: f32,
+    pub results: Vec<ModelBenchmark>,
+    pub recommended_model: Option<String>,
+}
+
+impl BenchmarkReport {
+    pub fn get_store_path(app: &AppHandle) -> Result<PathBuf, String> {
+        let app_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+        if !app_dir.exists() {
+            std::fs::create_dir_all(&app_dir)
+                .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+        }
+
+        Ok(app_dir.join("benchmark.json"))
+    }
+
+    pub fn load(app: &AppHandle) -> Result<Option<Self>, String> {
+        let path = Self::get_store_path(app)?;
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let data = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read benchmark file: {}", e))?;
+
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse benchmark results: {}", e))
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let path = Self::get_store_path(app)?;
+
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize benchmark results: {}", e))?;
+
+        std::fs::write(&path, data)
+            .map_err(|e| format!("Failed to write benchmark file: {}", e))
+    }
+
+    /// Picks the most accurate model that still runs well under real time,
+    /// falling back to the fastest one if nothing qualifies.
+    fn pick_recommended(results: &[ModelBenchmark]) -> Option<String> {
+        let fast_enough = results
+            .iter()
+            .filter(|r| r.real_time_factor <= MAX_RECOMMENDED_RTF)
+            .min_by(|a, b| {
+                a.word_error_rate
+                    .total_cmp(&b.word_error_rate)
+                    .then(a.real_time_factor.total_cmp(&b.real_time_factor))
+            });
+
+        fast_enough
+            .or_else(|| {
+                results
+                    .iter()
+                    .min_by(|a, b| a.real_time_factor.total_cmp(&b.real_time_factor))
+            })
+            .map(|r| r.model_id.clone())
+    }
+}
+
+fn reference_clip_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let bundled = app
+        .path()
+        .resource_dir()
+        .map_err(|e| format!("Failed to get resource dir: {}", e))?
+        .join("benchmark")
+        .join(REFERENCE_CLIP);
+
+    if bundled.exists() {
+        return Ok(bundled);
+    }
+
+    // Debug builds can also read it straight from the source tree.
+    #[cfg(debug_assertions)]
+    {
+        let source = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("benchmark")
+            .join(REFERENCE_CLIP);
+        if source.exists() {
+            return Ok(source);
+        }
+    }
+
+    Err(format!(
+        "Reference clip not found: {:?} (see the Benchmark section of the README)",
+        bundled
+    ))
+}
+
+pub fn run_benchmark(app: &AppHandle) -> Result<BenchmarkReport, String> {
+    let clip_path = reference_clip_path(app)?;
+    let (audio_data, sample_rate) = read_wav_mono(&clip_path)?;
+    let clip_duration_secs = audio_data.len() as f32 / sample_rate as f32;
+
+    let model_ids = WhisperModel::get_downloaded_models();
+    if model_ids.is_empty() {
+        return Err("No downloaded models to benchmark".to_string());
+    }
+
+    let cfg = WhisperRuntimeConfig {
+        language: Some("en".to_string()),
+        temperature: Some(0.0),
+        initial_prompt: None,
+        local_model: None,
+        cloud_model: None,
+    };
+
+    let total = model_ids.len() as u32;
+    let mut results = Vec::new();
+
+    for (idx, model_id) in model_ids.into_iter().enumerate() {
+        println!("🏁 Benchmarking model: {}", model_id);
+        let sampler = MemorySampler::start();
+
+        let load_start = std::time::Instant::now();
+        let mut model = WhisperModel::new();
+        if let Err(e) = model.load_model(Some(model_id.clone())) {
+            println!("❌ Skipping {}: {}", model_id, e);
+            sampler.stop();
+            continue;
+        }
+        let load_time = load_start.elapsed();
+
+        let transcribe_start = std::time::Instant::now();
+        let transcribe_result = model.transcribe_with_config(&audio_data, sample_rate, &cfg);
+        let transcribe_time = transcribe_start.elapsed();
+
+        drop(model);
+        let peak_bytes = sampler.stop();
+
+        let transcript = match transcribe_result {
+            Ok(text) => text,
+            Err(e) => {
+                println!("❌ Skipping {}: {}", model_id, e);
+                continue;
+            }
+        };
+
+        let result = ModelBenchmark {
+            model_id: model_id.clone(),
+            load_time_ms: load_time.as_secs_f64() * 1000.0,
+            transcribe_time_ms: transcribe_time.as_secs_f64() * 1000.0,
+            real_time_factor: transcribe_time.as_secs_f32() / clip_duration_secs,
+            peak_memory_mb: peak_bytes as f64 / (1024.0 * 1024.0),
+            word_error_rate: metrics::word_error_rate(REFERENCE_TEXT, &transcript).rate,
+            transcript,
+        };
+        println!(
+            "📊 {}: load {:.0}ms, RTF {:.2}x, peak {:.0} MB, WER {:.1}%",
+            result.model_id,
+            result.load_time_ms,
+            result.real_time_factor,
+            result.peak_memory_mb,
+            result.word_error_rate * 100.0
+        );
+        results.push(result);
+
+        BenchmarkProgress {
+            model_id,
+            completed: idx as u32 + 1,
+            total,
+        }
+        .emit(app)
+        .ok();
+    }
+
+    let report = BenchmarkReport {
+        run_at: chrono::Utc::now().timestamp_millis() as f64,
+        clip_duration_secs,
+        recommended_model: BenchmarkReport::pick_recommended(&results),
+        results,
+    };
+    report.save(app)?;
+
+    Ok(report)
+}
+
+/// Polls resident memory on a background thread and reports the high-water mark
+/// above the baseline taken when sampling started.
+struct MemorySampler {
+    running: Arc<AtomicBool>,
+    handle: std::thread::JoinHandle<u64>,
+}
+
+impl MemorySampler {
+    fn start() -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let running_clone = running.clone();
+        let handle = std::thread::spawn(move || {
+            let baseline = current_rss_bytes().unwrap_or(0);
+            let mut peak = baseline;
+            while running_clone.load(Ordering::SeqCst) {
+                if let Some(rss) = current_rss_bytes() {
+                    peak = peak.max(rss);
+                }
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            peak.saturating_sub(baseline)
+        });
+        Self { running, handle }
+    }
+
+    fn stop(self) -> u64 {
+        self.running.store(false, Ordering::SeqCst);
+        self.handle.join().unwrap_or(0)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn current_rss_bytes() -> Option<u64> {
+    let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+    let written = unsafe {
+        libc::proc_pidinfo(
+            std::process::id() as libc::c_int,
+            libc::PROC_PIDTASKINFO,
+            0,
+            &mut info as *mut _ as *mut libc::c_void,
+            size,
+        )
+    };
+    if written == size {
+        Some(info.pti_resident_size)
+    } else {
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn current_rss_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let resident_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    Some(resident_pages * page_size.max(0) as u64)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn current_rss_bytes() -> Option<u64> {
+    None
+}
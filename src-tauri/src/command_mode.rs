@@ -0,0 +1,83 @@
+use std::sync::Mutex;
+use tauri::async_runtime::JoinHandle;
+
+/// Capturing the selection and replacing it are only implemented for macOS.
+pub const SUPPORTED: bool = cfg!(target_os = "macos");
+
+pub const SYSTEM_PROMPT: &str = "You edit text on the user's behalf. The user message holds a spoken instruction \
+and the selected text it applies to. Carry out the instruction on the text, keeping its language unless the \
+instruction says otherwise.";
+
+pub fn user_message(instruction: &str, selection: &str) -> String {
+    format!("Instruction: {}\n\nText:\n{}", instruction.trim(), selection)
+}
+
+/// The selection captured when the command hotkey went down. Capturing runs
+/// alongside the recording, so releasing the key waits on it.
+#[derive(Default)]
+pub struct CommandState {
+    pub selection: Mutex<Option<JoinHandle<Result<Option<String>, String>>>>,
+}
+
+/// Copies the focused app's selection through the clipboard, then puts the
+/// clipboard back. `None` means nothing was selected.
+#[cfg(target_os = "macos")]
+pub fn capture_selection() -> Result<Option<String>, String> {
+    use crate::editing::keys;
+    use cocoa::appkit::NSPasteboardTypeString;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
+    use core_graphics::event::CGEventFlags;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let pool = NSAutoreleasePool::new(nil);
+        let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+        let old_items: id = msg_send![pasteboard, readObjectsForClasses:nil options:nil];
+        let before: isize = msg_send![pasteboard, changeCount];
+
+        let copied = keys::press(keys::C, CGEventFlags::CGEventFlagCommand, 1);
+
+        // The copy lands asynchronously; with nothing selected it never does.
+        let mut changed = false;
+        if copied.is_ok() {
+            for _ in 0..50 {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                let count: isize = msg_send![pasteboard, changeCount];
+                if count != before {
+                    changed = true;
+                    break;
+                }
+            }
+        }
+
+        let selection = if changed {
+            let contents: id = msg_send![pasteboard, stringForType: NSPasteboardTypeString];
+            if contents == nil {
+                None
+            } else {
+                let bytes = contents.UTF8String();
+                Some(std::ffi::CStr::from_ptr(bytes).to_string_lossy().into_owned())
+            }
+        } else {
+            None
+        };
+
+        if changed {
+            // An empty clipboard stays empty rather than keeping the selection.
+            let _: () = msg_send![pasteboard, clearContents];
+            if old_items != nil {
+                let _: () = msg_send![pasteboard, writeObjects: old_items];
+            }
+        }
+
+        let _: () = msg_send![pool, release];
+        copied?;
+        Ok(selection.filter(|s| !s.trim().is_empty()))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn capture_selection() -> Result<Option<String>, String> {
+    Err("Capturing the selection is not implemented for this platform".to_string())
+}
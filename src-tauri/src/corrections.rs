@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+// Longer runs, or runs that grow or shrink by more than a word, are rewrites
+// rather than corrections of a misheard word.
+const MAX_RUN_WORDS: usize = 3;
+// Keeps the diff table small; transcripts longer than this aren't learned from.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_min_occurrences() -> u32 {
+    3
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct CorrectionSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// How often the same substitution has to be seen before it's suggested.
+    #[serde(default = "default_min_occurrences")]
+    pub min_occurrences: u32,
+}
+
+impl Default for CorrectionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_occurrences: default_min_occurrences(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    /// Rewrite `from` to `to` after transcription.
+    ReplacementRule,
+    /// Add `to` to the vocabulary prompt so Whisper spells it right to begin with.
+    Vocabulary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ObservedCorrection {
+    pub from: String, // Lowercased, since replacement rules match case-insensitively
+    pub to: String,
+    pub count: u32,
+    pub last_seen: f64, // Unix timestamp in milliseconds
+    #[serde(default)]
+    pub dismissed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct CorrectionSuggestion {
+    pub from: String,
+    pub to: String,
+    pub count: u32,
+    pub recommended: SuggestionKind,
+}
+
+impl ObservedCorrection {
+    /// Names and jargon ("Tauri", "macOS", "k8s") are better taught through the
+    /// prompt; everything else becomes a replacement rule.
+    fn recommended_kind(&self) -> SuggestionKind {
+        let is_term = !self.to.contains(' ')
+            && self
+                .to
+                .chars()
+                .any(|c| c.is_uppercase() || c.is_ascii_digit());
+        if is_term {
+            SuggestionKind::Vocabulary
+        } else {
+            SuggestionKind::ReplacementRule
+        }
+    }
+}
+
+fn strip_punctuation(token: &str) -> &str {
+    token.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+}
+
+/// Word-level substitutions between `before` and `after`, as (from, to) pairs.
+/// Words compare case-sensitively so "cap" → "Cap" counts, but punctuation is
+/// ignored. Pure insertions and deletions aren't substitutions and are skipped.
+pub fn word_substitutions(before: &str, after: &str) -> Vec<(String, String)> {
+    let a: Vec<&str> = before
+        .split_whitespace()
+        .map(strip_punctuation)
+        .filter(|w| !w.is_empty())
+        .collect();
+    let b: Vec<&str> = after
+        .split_whitespace()
+        .map(strip_punctuation)
+        .filter(|w| !w.is_empty())
+        .collect();
+    if a.is_empty() || b.is_empty() || (a.len() + 1) * (b.len() + 1) > MAX_DIFF_CELLS {
+        return Vec::new();
+    }
+
+    // Longest common subsequence table, filled from the end so the walk below
+    // can go front to back.
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut substitutions = Vec::new();
+    let mut removed: Vec<&str> = Vec::new();
+    let mut added: Vec<&str> = Vec::new();
+    let mut flush = |removed: &mut Vec<&str>, added: &mut Vec<&str>| {
+        if !removed.is_empty()
+            && !added.is_empty()
+            && removed.len() <= MAX_RUN_WORDS
+            && added.len() <= MAX_RUN_WORDS
+            && removed.len().abs_diff(added.len()) <= 1
+        {
+            substitutions.push((removed.join(" "), added.join(" ")));
+        }
+        removed.clear();
+        added.clear();
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            flush(&mut removed, &mut added);
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            added.push(b[j]);
+            j += 1;
+        } else {
+            removed.push(a[i]);
+            i += 1;
+        }
+    }
+    flush(&mut removed, &mut added);
+    substitutions
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CorrectionStore {
+    pub observed: Vec<ObservedCorrection>,
+}
+
+impl CorrectionStore {
+    pub fn get_store_path(app: &AppHandle) -> Result<PathBuf, String> {
+        let app_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+        if !app_dir.exists() {
+            std::fs::create_dir_all(&app_dir)
+                .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+        }
+
+        Ok(app_dir.join("corrections.json"))
+    }
+
+    pub fn load(app: &AppHandle) -> Result<Self, String> {
+        let path = Self::get_store_path(app)?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let data = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read corrections file: {}", e))?;
+
+        serde_json::from_str(&data).map_err(|e| format!("Failed to parse corrections: {}", e))
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let path = Self::get_store_path(app)?;
+
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize corrections: {}", e))?;
+
+        std::fs::write(&path, data).map_err(|e| format!("Failed to write corrections file: {}", e))
+    }
+
+    pub fn record(&mut self, substitutions: &[(String, String)]) {
+        let now = chrono::Utc::now().timestamp_millis() as f64;
+        for (from, to) in substitutions {
+            let from = from.to_lowercase();
+            match self.observed.iter_mut().find(|o| o.from == from && o.to == *to) {
+                Some(observed) => {
+                    observed.count += 1;
+                    observed.last_seen = now;
+                }
+                None => self.observed.push(ObservedCorrection {
+                    from,
+                    to: to.clone(),
+                    count: 1,
+                    last_seen: now,
+                    dismissed: false,
+                }),
+            }
+        }
+    }
+
+    /// Substitutions seen at least `min_occurrences` times that haven't been
+    /// dismissed, most frequent first.
+    pub fn suggestions(&self, min_occurrences: u32) -> Vec<CorrectionSuggestion> {
+        let mut suggestions: Vec<CorrectionSuggestion> = self
+            .observed
+            .iter()
+            .filter(|o| !o.dismissed && o.count >= min_occurrences.max(1))
+            .map(|o| CorrectionSuggestion {
+                from: o.from.clone(),
+                to: o.to.clone(),
+                count: o.count,
+                recommended: o.recommended_kind(),
+            })
+            .collect();
+        suggestions.sort_by(|a, b| b.count.cmp(&a.count));
+        suggestions
+    }
+
+    /// Forgets an accepted suggestion, so it starts counting afresh if the
+    /// fix doesn't stick.
+    pub fn resolve(&mut self, from: &str, to: &str) {
+        let from = from.to_lowercase();
+        self.observed.retain(|o| !(o.from == from && o.to == to));
+    }
+
+    pub fn dismiss(&mut self, from: &str, to: &str) -> Result<(), String> {
+        let from = from.to_lowercase();
+        let observed = self
+            .observed
+            .iter_mut()
+            .find(|o| o.from == from && o.to == to)
+            .ok_or_else(|| "Correction not found".to_string())?;
+        observed.dismissed = true;
+        Ok(())
+    }
+}
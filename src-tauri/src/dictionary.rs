@@ -0,0 +1,285 @@
+use regex::{Captures, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    /// Matches anywhere, including inside other words.
+    Literal,
+    WholeWord,
+    /// `find` is a regular expression; `replace` may refer to groups as `$1` or `${name}`.
+    Regex,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ReplacementRule {
+    #[serde(default)]
+    pub id: String,
+    pub find: String,
+    pub replace: String,
+    pub mode: MatchMode,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Carries the matched text's casing over: "TORI" becomes "TAURI" and
+    /// "Tori" becomes "Tauri". Lowercase matches keep `replace` as written.
+    #[serde(default)]
+    pub preserve_case: bool,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub hit_count: u32,
+    #[serde(default)]
+    pub last_hit_at: Option<f64>, // Unix timestamp in milliseconds
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl ReplacementRule {
+    fn compile(&self) -> Result<Regex, String> {
+        let pattern = match self.mode {
+            MatchMode::Literal => regex::escape(&self.find),
+            MatchMode::WholeWord => {
+                // \b only makes sense next to a word character; "C++" still has to
+                // match before a space.
+                let start = if self.find.starts_with(is_word_char) { r"\b" } else { "" };
+                let end = if self.find.ends_with(is_word_char) { r"\b" } else { "" };
+                format!("{}{}{}", start, regex::escape(&self.find), end)
+            }
+            MatchMode::Regex => self.find.clone(),
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map_err(|e| format!("Invalid pattern \"{}\": {}", self.find, e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.find.is_empty() {
+            return Err("Replacement rule needs something to find".to_string());
+        }
+        self.compile().map(|_| ())
+    }
+}
+
+fn match_case(replacement: &str, matched: &str) -> String {
+    let letters: Vec<char> = matched.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        return replacement.to_uppercase();
+    }
+    if letters.first().is_some_and(|c| c.is_uppercase()) {
+        let mut chars = replacement.chars();
+        if let Some(first) = chars.next() {
+            return first.to_uppercase().chain(chars).collect();
+        }
+    }
+    replacement.to_string()
+}
+
+/// Runs every enabled rule over `text` in order, bumping hit counters as it
+/// goes. Returns whether any rule fired, so callers know to save the counters.
+pub fn apply(text: &str, rules: &mut [ReplacementRule]) -> (String, bool) {
+    let mut text = text.to_string();
+    let mut fired = false;
+    let now = chrono::Utc::now().timestamp_millis() as f64;
+
+    for rule in rules.iter_mut().filter(|r| r.enabled) {
+        let regex = match rule.compile() {
+            Ok(regex) => regex,
+            Err(e) => {
+                eprintln!("Skipping replacement rule: {}", e);
+                continue;
+            }
+        };
+
+        let mut hits = 0;
+        let replaced = regex.replace_all(&text, |caps: &Captures| {
+            hits += 1;
+            let mut replacement = String::new();
+            if rule.mode == MatchMode::Regex {
+                caps.expand(&rule.replace, &mut replacement);
+            } else {
+                replacement.push_str(&rule.replace);
+            }
+            if rule.preserve_case {
+                match_case(&replacement, &caps[0])
+            } else {
+                replacement
+            }
+        });
+
+        if hits > 0 {
+            println!("📖 \"{}\" → \"{}\" ({}x)", rule.find, rule.replace, hits);
+            text = replaced.into_owned();
+            rule.hit_count += hits;
+            rule.last_hit_at = Some(now);
+            fired = true;
+        }
+    }
+    (text, fired)
+}
+
+/// One CSV row. Hit counters stay local and aren't exported.
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvRow {
+    find: String,
+    replace: String,
+    mode: MatchMode,
+    #[serde(default)]
+    case_sensitive: bool,
+    #[serde(default)]
+    preserve_case: bool,
+    #[serde(default = "default_true")]
+    enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
+pub struct DictionaryStore {
+    pub rules: Vec<ReplacementRule>,
+}
+
+impl DictionaryStore {
+    pub fn get_store_path(app: &AppHandle) -> Result<PathBuf, String> {
+        let app_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+        if !app_dir.exists() {
+            std::fs::create_dir_all(&app_dir)
+                .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+        }
+
+        Ok(app_dir.join("dictionary.json"))
+    }
+
+    pub fn load(app: &AppHandle) -> Result<Self, String> {
+        Self::load_from(&Self::get_store_path(app)?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read dictionary file: {}", e))?;
+
+        serde_json::from_str(&data).map_err(|e| format!("Failed to parse dictionary: {}", e))
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let path = Self::get_store_path(app)?;
+
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize dictionary: {}", e))?;
+
+        std::fs::write(&path, data).map_err(|e| format!("Failed to write dictionary file: {}", e))
+    }
+
+    /// Adds `rule`, or updates the rule with the same ID. Hit counters always
+    /// come from the stored rule.
+    pub fn upsert_rule(&mut self, mut rule: ReplacementRule) -> Result<ReplacementRule, String> {
+        rule.validate()?;
+
+        match self.rules.iter_mut().find(|r| !rule.id.is_empty() && r.id == rule.id) {
+            Some(existing) => {
+                rule.hit_count = existing.hit_count;
+                rule.last_hit_at = existing.last_hit_at;
+                *existing = rule.clone();
+            }
+            None => {
+                rule.id = uuid::Uuid::new_v4().to_string();
+                rule.hit_count = 0;
+                rule.last_hit_at = None;
+                self.rules.push(rule.clone());
+            }
+        }
+        Ok(rule)
+    }
+
+    pub fn delete_rule(&mut self, id: &str) -> Result<(), String> {
+        let initial_len = self.rules.len();
+        self.rules.retain(|r| r.id != id);
+
+        if self.rules.len() == initial_len {
+            Err("Replacement rule not found".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn reset_hit_counts(&mut self) {
+        for rule in &mut self.rules {
+            rule.hit_count = 0;
+            rule.last_hit_at = None;
+        }
+    }
+
+    pub fn export_csv(&self) -> Result<String, String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for rule in &self.rules {
+            writer
+                .serialize(CsvRow {
+                    find: rule.find.clone(),
+                    replace: rule.replace.clone(),
+                    mode: rule.mode,
+                    case_sensitive: rule.case_sensitive,
+                    preserve_case: rule.preserve_case,
+                    enabled: rule.enabled,
+                })
+                .map_err(|e| format!("Failed to write CSV: {}", e))?;
+        }
+        let data = writer
+            .into_inner()
+            .map_err(|e| format!("Failed to write CSV: {}", e))?;
+        String::from_utf8(data).map_err(|e| format!("Failed to write CSV: {}", e))
+    }
+
+    /// Appends the rules in `data`, skipping ones that already exist with the
+    /// same pattern and mode. Nothing is imported if any row is invalid.
+    /// Returns how many rules were added.
+    pub fn import_csv(&mut self, data: &str) -> Result<u32, String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::Headers)
+            .from_reader(data.as_bytes());
+
+        let mut imported = Vec::new();
+        for (i, row) in reader.deserialize::<CsvRow>().enumerate() {
+            // Row 1 is the header.
+            let row = row.map_err(|e| format!("Row {}: {}", i + 2, e))?;
+            let rule = ReplacementRule {
+                id: uuid::Uuid::new_v4().to_string(),
+                find: row.find,
+                replace: row.replace,
+                mode: row.mode,
+                case_sensitive: row.case_sensitive,
+                preserve_case: row.preserve_case,
+                enabled: row.enabled,
+                hit_count: 0,
+                last_hit_at: None,
+            };
+            rule.validate().map_err(|e| format!("Row {}: {}", i + 2, e))?;
+
+            let exists = self
+                .rules
+                .iter()
+                .chain(&imported)
+                .any(|r| r.find == rule.find && r.mode == rule.mode);
+            if !exists {
+                imported.push(rule);
+            }
+        }
+
+        let count = imported.len() as u32;
+        self.rules.extend(imported);
+        Ok(count)
+    }
+}
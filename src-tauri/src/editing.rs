@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum EditCommand {
+    /// Deletes the text inserted last.
+    ScratchThat,
+    DeleteLastWord,
+    /// Sends the app's own undo shortcut.
+    Undo,
+    SelectLastSentence,
+}
+
+const PHRASES: &[(&str, EditCommand)] = &[
+    ("scratch that", EditCommand::ScratchThat),
+    ("delete that", EditCommand::ScratchThat),
+    ("delete last word", EditCommand::DeleteLastWord),
+    ("delete the last word", EditCommand::DeleteLastWord),
+    ("undo", EditCommand::Undo),
+    ("undo that", EditCommand::Undo),
+    ("select last sentence", EditCommand::SelectLastSentence),
+    ("select the last sentence", EditCommand::SelectLastSentence),
+];
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_age_secs() -> u32 {
+    120
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct EditingSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Insertions older than this are forgotten, since the cursor has most
+    /// likely moved on and deleting blind would hit the wrong text.
+    #[serde(default = "default_max_age_secs")]
+    pub max_age_secs: u32,
+}
+
+impl Default for EditingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_age_secs: default_max_age_secs(),
+        }
+    }
+}
+
+/// Matches a whole utterance against the command phrases. Case and the
+/// punctuation Whisper adds ("Scratch that.") are ignored.
+pub fn parse_command(utterance: &str) -> Option<EditCommand> {
+    let normalized = utterance
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    PHRASES
+        .iter()
+        .find(|(phrase, _)| *phrase == normalized)
+        .map(|(_, command)| *command)
+}
+
+struct InsertedSpan {
+    text: String,
+    inserted_at: Instant,
+}
+
+/// Text inserted at the cursor during this session, newest last, so editing
+/// commands know what they are acting on.
+#[derive(Default)]
+pub struct InsertionHistory {
+    spans: Mutex<Vec<InsertedSpan>>,
+}
+
+impl InsertionHistory {
+    pub fn record(&self, text: &str) {
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(InsertedSpan {
+            text: text.to_string(),
+            inserted_at: Instant::now(),
+        });
+    }
+
+    fn take_last(&self, max_age: Duration) -> Option<String> {
+        let mut spans = self.spans.lock().unwrap();
+        spans.retain(|s| s.inserted_at.elapsed() <= max_age);
+        spans.pop().map(|s| s.text)
+    }
+
+    fn last(&self, max_age: Duration) -> Option<String> {
+        let mut spans = self.spans.lock().unwrap();
+        spans.retain(|s| s.inserted_at.elapsed() <= max_age);
+        spans.last().map(|s| s.text.clone())
+    }
+
+    /// Drops the last word from the newest span, forgetting the span once it's empty.
+    fn remove_last_word(&self) {
+        let mut spans = self.spans.lock().unwrap();
+        let Some(span) = spans.last_mut() else {
+            return;
+        };
+        let trimmed = span.text.trim_end();
+        let cut = trimmed
+            .rfind(char::is_whitespace)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        span.text.truncate(cut);
+        if span.text.trim().is_empty() {
+            spans.pop();
+        }
+    }
+}
+
+/// The last sentence of `text`, including trailing punctuation and spaces.
+fn last_sentence(text: &str) -> &str {
+    let body = text.trim_end_matches(|c: char| c.is_whitespace() || matches!(c, '.' | '!' | '?'));
+    let start = body
+        .rfind(|c: char| matches!(c, '.' | '!' | '?' | '\n'))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    text[start..].trim_start()
+}
+
+#[cfg(target_os = "macos")]
+pub mod keys {
+    use core_graphics::event::{CGEvent, CGEventFlags, CGEventTapLocation, CGKeyCode};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+    pub const DELETE: CGKeyCode = 0x33;
+    pub const LEFT_ARROW: CGKeyCode = 0x7B;
+    pub const Z: CGKeyCode = 0x06;
+    pub const C: CGKeyCode = 0x08;
+
+    pub fn press(key: CGKeyCode, flags: CGEventFlags, times: usize) -> Result<(), String> {
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| "Failed to create keyboard event source".to_string())?;
+        for _ in 0..times {
+            for down in [true, false] {
+                if let Ok(event) = CGEvent::new_keyboard_event(source.clone(), key, down) {
+                    event.set_flags(flags);
+                    event.post(CGEventTapLocation::HID);
+                }
+            }
+            // Some apps drop keystrokes that arrive faster than they can redraw.
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        Ok(())
+    }
+
+    /// Types `text` as keystrokes carrying the characters themselves, so the
+    /// keyboard layout doesn't matter.
+    pub fn type_text(text: &str) -> Result<(), String> {
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| "Failed to create keyboard event source".to_string())?;
+        let chars: Vec<char> = text.chars().collect();
+        // Events carry at most 20 UTF-16 units; 10 chars stay under that even
+        // when every one needs a surrogate pair.
+        for chunk in chars.chunks(10) {
+            let chunk: String = chunk.iter().collect();
+            for down in [true, false] {
+                if let Ok(event) = CGEvent::new_keyboard_event(source.clone(), 0, down) {
+                    event.set_string(&chunk);
+                    event.post(CGEventTapLocation::HID);
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        Ok(())
+    }
+}
+
+/// Carries out `command` against the most recent insertion by sending
+/// keystrokes to the focused app.
+pub fn run(history: &InsertionHistory, command: EditCommand, settings: &EditingSettings) -> Result<(), String> {
+    let max_age = Duration::from_secs(settings.max_age_secs as u64);
+    println!("✂️ Editing command: {:?}", command);
+
+    #[cfg(target_os = "macos")]
+    {
+        use core_graphics::event::CGEventFlags;
+
+        match command {
+            EditCommand::ScratchThat => {
+                let text = history
+                    .take_last(max_age)
+                    .ok_or_else(|| "Nothing recent to scratch".to_string())?;
+                keys::press(keys::DELETE, CGEventFlags::CGEventFlagNull, text.chars().count())?;
+            }
+            EditCommand::DeleteLastWord => {
+                if history.last(max_age).is_none() {
+                    return Err("Nothing recent to delete".to_string());
+                }
+                keys::press(keys::DELETE, CGEventFlags::CGEventFlagAlternate, 1)?;
+                history.remove_last_word();
+            }
+            EditCommand::Undo => {
+                keys::press(keys::Z, CGEventFlags::CGEventFlagCommand, 1)?;
+                // Pasting is a single undo step, so undo takes back the whole insertion.
+                history.take_last(max_age);
+            }
+            EditCommand::SelectLastSentence => {
+                let text = history
+                    .last(max_age)
+                    .ok_or_else(|| "Nothing recent to select".to_string())?;
+                let sentence = last_sentence(&text);
+                keys::press(keys::LEFT_ARROW, CGEventFlags::CGEventFlagShift, sentence.chars().count())?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (history, max_age);
+        Err("Editing commands not implemented for this platform".to_string())
+    }
+}
@@ -0,0 +1,274 @@
+use crate::audio::read_wav_mono;
+use crate::metrics::{self, ErrorRate};
+use crate::pipeline::{self, TextStages};
+use crate::whisper::WhisperModel;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalClipResult {
+    pub name: String,
+    pub reference: String,
+    pub hypothesis: String,
+    pub duration_secs: f32,
+    pub transcribe_ms: f64,
+    pub wer: ErrorRate,
+    pub cer: ErrorRate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub model_id: String,
+    pub language: Option<String>,
+    pub run_at: f64, // Unix timestamp in milliseconds
+    pub total_audio_secs: f32,
+    pub total_transcribe_ms: f64,
+    pub overall_wer: ErrorRate,
+    pub overall_cer: ErrorRate,
+    pub clips: Vec<EvalClipResult>,
+}
+
+pub struct EvalOptions {
+    pub corpus_dir: PathBuf,
+    pub output_dir: PathBuf,
+    pub model_id: String,
+    pub language: Option<String>,
+}
+
+impl EvalOptions {
+    /// Parses `--eval <dir> [--model <id>] [--language <code>] [--out <dir>]`.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut corpus_dir = None;
+        let mut output_dir = None;
+        let mut model_id = None;
+        let mut language = Some("en".to_string());
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || {
+                iter.next()
+                    .cloned()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+            match arg.as_str() {
+                "--eval" => corpus_dir = Some(PathBuf::from(value()?)),
+                "--out" => output_dir = Some(PathBuf::from(value()?)),
+                "--model" => model_id = Some(value()?),
+                "--language" => {
+                    let v = value()?;
+                    language = if v.eq_ignore_ascii_case("auto") { None } else { Some(v) };
+                }
+                other => return Err(format!("Unknown argument: {}", other)),
+            }
+        }
+
+        let corpus_dir = corpus_dir.ok_or_else(|| "Missing --eval <dir>".to_string())?;
+        let model_id = match model_id {
+            Some(id) => id,
+            None => WhisperModel::get_downloaded_models()
+                .into_iter()
+                .next()
+                .ok_or_else(|| "No downloaded models found; pass --model <id>".to_string())?,
+        };
+
+        Ok(Self {
+            output_dir: output_dir.unwrap_or_else(|| corpus_dir.clone()),
+            corpus_dir,
+            model_id,
+            language,
+        })
+    }
+}
+
+/// Entry point for headless evaluation runs, e.g. `talktype --eval ./corpus --model large-v3-turbo-q8_0`.
+/// The app itself isn't started, so this works without a display: settings are
+/// read from the app data directory and clips go through the same decoder and
+/// text stages as a single-pass dictation.
+pub fn run_cli(args: &[String]) -> Result<(), String> {
+    let options = EvalOptions::from_args(args)?;
+
+    // Hit counts stay in memory so eval runs don't show up in the user's dictionary stats.
+    let mut stages = TextStages::load_from_dir(&app_data_dir()?)?;
+    let mut model = WhisperModel::new();
+    model.load_model(Some(options.model_id.clone()))?;
+
+    let report = run_eval(&model, &mut stages, &options)?;
+    write_reports(&report, &options.output_dir)?;
+
+    println!(
+        "✅ Evaluated {} clips with {}: WER {:.2}%, CER {:.2}%",
+        report.clips.len(),
+        report.model_id,
+        report.overall_wer.rate * 100.0,
+        report.overall_cer.rate * 100.0
+    );
+    Ok(())
+}
+
+/// The directory `app.path().app_data_dir()` resolves to in the running app.
+fn app_data_dir() -> Result<PathBuf, String> {
+    let identifier = crate::context().config().identifier.clone();
+    dirs::data_dir()
+        .map(|dir| dir.join(identifier))
+        .ok_or_else(|| "Failed to get app data dir".to_string())
+}
+
+/// Finds `name.wav` files that have a matching `name.txt` reference transcript.
+fn collect_clips(dir: &Path) -> Result<Vec<(String, PathBuf, String)>, String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read corpus dir {}: {}", dir.display(), e))?;
+
+    let mut clips = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let is_wav = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("wav"))
+            .unwrap_or(false);
+        if !is_wav {
+            continue;
+        }
+
+        let reference_path = path.with_extension("txt");
+        if !reference_path.exists() {
+            println!("⚠️ Skipping {} - no reference transcript", path.display());
+            continue;
+        }
+
+        let reference = std::fs::read_to_string(&reference_path)
+            .map_err(|e| format!("Failed to read {}: {}", reference_path.display(), e))?;
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        clips.push((name, path, reference.trim().to_string()));
+    }
+
+    clips.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(clips)
+}
+
+pub fn run_eval(
+    model: &WhisperModel,
+    stages: &mut TextStages,
+    options: &EvalOptions,
+) -> Result<EvalReport, String> {
+    let clips = collect_clips(&options.corpus_dir)?;
+    if clips.is_empty() {
+        return Err(format!(
+            "No wav/txt pairs found in {}",
+            options.corpus_dir.display()
+        ));
+    }
+
+    // No carryover from recent dictations, so scores are comparable across runs.
+    let mut cfg = crate::runtime_config_from_settings(&stages.settings, None, None);
+    cfg.language = options.language.clone();
+
+    let mut results = Vec::new();
+    for (name, path, reference) in clips {
+        println!("🎧 Evaluating {}", name);
+        let (audio_data, sample_rate) = read_wav_mono(&path)?;
+        let duration_secs = audio_data.len() as f32 / sample_rate as f32;
+
+        let transcribe_start = std::time::Instant::now();
+        let hypothesis = if pipeline::has_meaningful_audio(&audio_data, pipeline::peak_level(&audio_data)) {
+            let output = model.transcribe_detailed(&audio_data, sample_rate, &cfg)?;
+            match pipeline::clean_output(output) {
+                Some(output) => stages.apply(&output.text, output.language.as_deref(), None).0,
+                None => String::new(),
+            }
+        } else {
+            String::new()
+        };
+        let transcribe_ms = transcribe_start.elapsed().as_secs_f64() * 1000.0;
+
+        results.push(EvalClipResult {
+            wer: metrics::word_error_rate(&reference, &hypothesis),
+            cer: metrics::char_error_rate(&reference, &hypothesis),
+            name,
+            reference,
+            hypothesis,
+            duration_secs,
+            transcribe_ms,
+        });
+    }
+
+    Ok(EvalReport {
+        model_id: options.model_id.clone(),
+        language: options.language.clone(),
+        run_at: chrono::Utc::now().timestamp_millis() as f64,
+        total_audio_secs: results.iter().map(|r| r.duration_secs).sum(),
+        total_transcribe_ms: results.iter().map(|r| r.transcribe_ms).sum(),
+        overall_wer: ErrorRate::sum(results.iter().map(|r| &r.wer)),
+        overall_cer: ErrorRate::sum(results.iter().map(|r| &r.cer)),
+        clips: results,
+    })
+}
+
+pub fn write_reports(report: &EvalReport, output_dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Failed to create output dir: {}", e))?;
+
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize eval report: {}", e))?;
+    let json_path = output_dir.join("eval-report.json");
+    std::fs::write(&json_path, json)
+        .map_err(|e| format!("Failed to write {}: {}", json_path.display(), e))?;
+
+    let md_path = output_dir.join("eval-report.md");
+    std::fs::write(&md_path, render_markdown(report))
+        .map_err(|e| format!("Failed to write {}: {}", md_path.display(), e))?;
+
+    println!("📝 Wrote {} and {}", json_path.display(), md_path.display());
+    Ok(())
+}
+
+fn render_markdown(report: &EvalReport) -> String {
+    let mut md = String::new();
+    let _ = writeln!(md, "# Evaluation report\n");
+    let _ = writeln!(md, "- Model: `{}`", report.model_id);
+    let _ = writeln!(
+        md,
+        "- Language: {}",
+        report.language.as_deref().unwrap_or("auto")
+    );
+    let _ = writeln!(md, "- Clips: {}", report.clips.len());
+    let _ = writeln!(md, "- Audio: {:.1}s", report.total_audio_secs);
+    let rtf = if report.total_audio_secs > 0.0 {
+        report.total_transcribe_ms as f32 / 1000.0 / report.total_audio_secs
+    } else {
+        0.0
+    };
+    let _ = writeln!(md, "- RTF: {:.2}x", rtf);
+    let _ = writeln!(
+        md,
+        "- Overall WER: {:.2}% ({}/{})",
+        report.overall_wer.rate * 100.0,
+        report.overall_wer.errors,
+        report.overall_wer.reference_len
+    );
+    let _ = writeln!(
+        md,
+        "- Overall CER: {:.2}% ({}/{})\n",
+        report.overall_cer.rate * 100.0,
+        report.overall_cer.errors,
+        report.overall_cer.reference_len
+    );
+
+    let _ = writeln!(md, "| Clip | Duration | WER | CER | Hypothesis |");
+    let _ = writeln!(md, "|---|---|---|---|---|");
+    for clip in &report.clips {
+        let _ = writeln!(
+            md,
+            "| {} | {:.1}s | {:.2}% | {:.2}% | {} |",
+            clip.name,
+            clip.duration_secs,
+            clip.wer.rate * 100.0,
+            clip.cer.rate * 100.0,
+            clip.hypothesis.replace('|', "\\|")
+        );
+    }
+    md
+}
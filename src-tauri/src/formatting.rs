@@ -0,0 +1,308 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A spoken phrase and the text it turns into.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct FormattingCommand {
+    pub phrase: String,
+    pub text: String,
+    /// Whether a space separates the inserted text from the previous word.
+    pub space_before: bool,
+    /// Whether a space separates the inserted text from the next word.
+    pub space_after: bool,
+    #[serde(default)]
+    pub capitalize_next: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct LanguageCommands {
+    /// Saying this before a command phrase inserts the phrase itself, e.g. "literally period".
+    pub escape_word: String,
+    pub commands: Vec<FormattingCommand>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct FormattingSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Extra commands keyed by language code. A custom command with the same
+    /// phrase as a built-in one replaces it.
+    #[serde(default)]
+    pub custom_commands: HashMap<String, Vec<FormattingCommand>>,
+}
+
+impl Default for FormattingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            custom_commands: HashMap::new(),
+        }
+    }
+}
+
+fn punctuation(phrase: &str, text: &str, capitalize_next: bool) -> FormattingCommand {
+    FormattingCommand {
+        phrase: phrase.to_string(),
+        text: text.to_string(),
+        space_before: false,
+        space_after: true,
+        capitalize_next,
+    }
+}
+
+fn layout(phrase: &str, text: &str) -> FormattingCommand {
+    FormattingCommand {
+        phrase: phrase.to_string(),
+        text: text.to_string(),
+        space_before: false,
+        space_after: false,
+        capitalize_next: true,
+    }
+}
+
+fn open_quote(phrase: &str) -> FormattingCommand {
+    FormattingCommand {
+        phrase: phrase.to_string(),
+        text: "\"".to_string(),
+        space_before: true,
+        space_after: false,
+        capitalize_next: false,
+    }
+}
+
+fn close_quote(phrase: &str) -> FormattingCommand {
+    FormattingCommand {
+        phrase: phrase.to_string(),
+        text: "\"".to_string(),
+        space_before: false,
+        space_after: true,
+        capitalize_next: false,
+    }
+}
+
+/// Built-in command table for a language code. Unknown languages get English.
+pub fn builtin_commands(language: &str) -> LanguageCommands {
+    match language {
+        "de" => LanguageCommands {
+            escape_word: "wörtlich".to_string(),
+            commands: vec![
+                punctuation("punkt", ".", true),
+                punctuation("komma", ",", false),
+                punctuation("fragezeichen", "?", true),
+                punctuation("ausrufezeichen", "!", true),
+                punctuation("doppelpunkt", ":", false),
+                punctuation("semikolon", ";", false),
+                layout("neue zeile", "\n"),
+                layout("neuer absatz", "\n\n"),
+                layout("aufzählungspunkt", "\n- "),
+                open_quote("anführungszeichen auf"),
+                close_quote("anführungszeichen zu"),
+            ],
+        },
+        "es" => LanguageCommands {
+            escape_word: "literalmente".to_string(),
+            commands: vec![
+                punctuation("punto", ".", true),
+                punctuation("coma", ",", false),
+                punctuation("signo de interrogación", "?", true),
+                punctuation("signo de exclamación", "!", true),
+                punctuation("dos puntos", ":", false),
+                punctuation("punto y coma", ";", false),
+                layout("nueva línea", "\n"),
+                layout("nuevo párrafo", "\n\n"),
+                layout("viñeta", "\n- "),
+                open_quote("abrir comillas"),
+                close_quote("cerrar comillas"),
+            ],
+        },
+        "fr" => LanguageCommands {
+            escape_word: "littéralement".to_string(),
+            commands: vec![
+                punctuation("point", ".", true),
+                punctuation("virgule", ",", false),
+                punctuation("point d'interrogation", "?", true),
+                punctuation("point d'exclamation", "!", true),
+                punctuation("deux points", ":", false),
+                punctuation("point-virgule", ";", false),
+                layout("nouvelle ligne", "\n"),
+                layout("nouveau paragraphe", "\n\n"),
+                layout("puce", "\n- "),
+                open_quote("ouvrez les guillemets"),
+                close_quote("fermez les guillemets"),
+            ],
+        },
+        _ => LanguageCommands {
+            escape_word: "literally".to_string(),
+            commands: vec![
+                punctuation("period", ".", true),
+                punctuation("full stop", ".", true),
+                punctuation("comma", ",", false),
+                punctuation("question mark", "?", true),
+                punctuation("exclamation mark", "!", true),
+                punctuation("exclamation point", "!", true),
+                punctuation("colon", ":", false),
+                punctuation("semicolon", ";", false),
+                layout("new line", "\n"),
+                layout("new paragraph", "\n\n"),
+                layout("bullet point", "\n- "),
+                open_quote("open quote"),
+                close_quote("close quote"),
+                close_quote("end quote"),
+            ],
+        },
+    }
+}
+
+/// Built-in commands for `language` with the user's additions merged in.
+pub fn commands_for(language: &str, settings: &FormattingSettings) -> LanguageCommands {
+    let mut table = builtin_commands(language);
+    if let Some(custom) = settings.custom_commands.get(language) {
+        for command in custom {
+            let phrase = command.phrase.to_lowercase();
+            table.commands.retain(|c| c.phrase.to_lowercase() != phrase);
+            table.commands.push(command.clone());
+        }
+    }
+    table
+}
+
+fn core_word(token: &str) -> String {
+    token
+        .trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+        .to_lowercase()
+}
+
+const PUNCTUATION: [char; 6] = ['.', ',', '?', '!', ':', ';'];
+
+fn is_punctuation(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| PUNCTUATION.contains(&c))
+}
+
+/// Whisper marks the pauses it hears with punctuation, so a token ending in
+/// punctuation closes a clause.
+fn ends_clause(token: &str) -> bool {
+    token.trim_end_matches(['"', '\'', ')']).ends_with(PUNCTUATION)
+}
+
+struct Output {
+    text: String,
+    glue_next: bool,
+    capitalize_next: bool,
+}
+
+impl Output {
+    fn push_word(&mut self, word: &str) {
+        if !self.text.is_empty() && !self.glue_next && !self.text.ends_with(char::is_whitespace) {
+            self.text.push(' ');
+        }
+        let mut chars = word.chars();
+        if self.capitalize_next {
+            if let Some(first) = chars.next() {
+                self.text.extend(first.to_uppercase());
+            }
+        }
+        self.text.push_str(chars.as_str());
+        self.glue_next = false;
+        self.capitalize_next = false;
+    }
+
+    fn push_command(&mut self, command: &FormattingCommand) {
+        if command.space_before {
+            if !self.text.is_empty() && !self.text.ends_with(char::is_whitespace) {
+                self.text.push(' ');
+            }
+        } else {
+            let trimmed = self.text.trim_end_matches(' ').len();
+            self.text.truncate(trimmed);
+            // Whisper often guesses punctuation of its own ("Hello, comma"); the
+            // spoken command wins.
+            if is_punctuation(&command.text) {
+                let trimmed = self.text.trim_end_matches(PUNCTUATION).len();
+                self.text.truncate(trimmed);
+            }
+        }
+        // A bullet right after "new line" shouldn't add a second line break.
+        let mut text = command.text.as_str();
+        let content = text.trim_start_matches('\n');
+        if !content.is_empty() && (self.text.is_empty() || self.text.ends_with('\n')) {
+            text = content;
+        }
+        self.text.push_str(text);
+        self.glue_next = !command.space_after;
+        self.capitalize_next = command.capitalize_next;
+    }
+}
+
+/// Replaces spoken formatting commands in `text` with the characters they
+/// stand for. Phrases match case-insensitively and ignore punctuation Whisper
+/// attached to them; the longest phrase wins when several match.
+///
+/// A phrase only counts as a command at a clause boundary: at the start or end
+/// of the utterance, next to a pause Whisper punctuated, or next to another
+/// command. In running speech ("the trial period ends") it stays a word.
+pub fn apply(text: &str, table: &LanguageCommands) -> String {
+    let mut commands: Vec<(Vec<String>, &FormattingCommand)> = table
+        .commands
+        .iter()
+        .map(|c| (c.phrase.split_whitespace().map(|w| w.to_lowercase()).collect::<Vec<_>>(), c))
+        .filter(|(words, _)| !words.is_empty())
+        .collect();
+    commands.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+    let escape_word = table.escape_word.to_lowercase();
+
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let cores: Vec<String> = tokens.iter().map(|t| core_word(t)).collect();
+    let mut output = Output {
+        text: String::with_capacity(text.len()),
+        glue_next: false,
+        capitalize_next: false,
+    };
+
+    let match_at = |i: usize| {
+        commands.iter().find(|(words, _)| {
+            i + words.len() <= cores.len() && words.iter().zip(&cores[i..]).all(|(w, c)| w == c)
+        })
+    };
+
+    // Whether the previous token closed a clause, or was a command itself.
+    let mut at_boundary = true;
+    let mut i = 0;
+    while i < tokens.len() {
+        if !escape_word.is_empty() && cores[i] == escape_word {
+            if let Some((words, _)) = match_at(i + 1) {
+                // Say the phrase as plain words, without Whisper's punctuation.
+                for core in &cores[i + 1..i + 1 + words.len()] {
+                    output.push_word(core);
+                }
+                i += 1 + words.len();
+                at_boundary = ends_clause(tokens[i - 1]);
+                continue;
+            }
+        }
+
+        let command = match_at(i).filter(|(words, command)| {
+            let end = i + words.len();
+            // Nothing to punctuate before the first word.
+            let after_boundary = at_boundary && (i > 0 || !is_punctuation(&command.text));
+            after_boundary || end == tokens.len() || ends_clause(tokens[end - 1]) || match_at(end).is_some()
+        });
+        match command {
+            Some((words, command)) => {
+                output.push_command(command);
+                i += words.len();
+                at_boundary = true;
+            }
+            None => {
+                output.push_word(tokens[i]);
+                at_boundary = ends_clause(tokens[i]);
+                i += 1;
+            }
+        }
+    }
+
+    output.text
+}
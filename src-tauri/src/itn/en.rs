@@ -0,0 +1,553 @@
+use super::{tokenize, ItnSettings, Rewriter, Token};
+
+const SMALL: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen",
+    "nineteen",
+];
+const TENS: &[&str] = &["twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+const SMALL_ORDINALS: &[&str] = &[
+    "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth", "tenth",
+    "eleventh", "twelfth", "thirteenth", "fourteenth", "fifteenth", "sixteenth", "seventeenth",
+    "eighteenth", "nineteenth",
+];
+const TENS_ORDINALS: &[&str] = &[
+    "twentieth", "thirtieth", "fortieth", "fiftieth", "sixtieth", "seventieth", "eightieth",
+    "ninetieth",
+];
+const MONTHS: &[&str] = &[
+    "January", "February", "March", "April", "May", "June", "July", "August", "September",
+    "October", "November", "December",
+];
+const CURRENCIES: &[(&str, &str)] = &[
+    ("dollar", "$"),
+    ("dollars", "$"),
+    ("bucks", "$"),
+    ("euro", "€"),
+    ("euros", "€"),
+    ("yen", "¥"),
+];
+// Multi-word names come before their prefixes so the longest one wins.
+const UNITS: &[(&str, &str)] = &[
+    ("kilometers per hour", "km/h"),
+    ("kilometres per hour", "km/h"),
+    ("miles per hour", "mph"),
+    ("degrees celsius", "°C"),
+    ("degrees fahrenheit", "°F"),
+    ("degrees", "°"),
+    ("kilometers", "km"),
+    ("kilometres", "km"),
+    ("kilometer", "km"),
+    ("kilometre", "km"),
+    ("centimeters", "cm"),
+    ("centimetres", "cm"),
+    ("centimeter", "cm"),
+    ("centimetre", "cm"),
+    ("millimeters", "mm"),
+    ("millimetres", "mm"),
+    ("millimeter", "mm"),
+    ("millimetre", "mm"),
+    ("meters", "m"),
+    ("metres", "m"),
+    ("meter", "m"),
+    ("metre", "m"),
+    ("kilograms", "kg"),
+    ("kilogram", "kg"),
+    ("milligrams", "mg"),
+    ("milligram", "mg"),
+    ("grams", "g"),
+    ("gram", "g"),
+    ("milliliters", "mL"),
+    ("millilitres", "mL"),
+    ("milliliter", "mL"),
+    ("millilitre", "mL"),
+    ("liters", "L"),
+    ("litres", "L"),
+    ("liter", "L"),
+    ("litre", "L"),
+    ("kilobytes", "KB"),
+    ("megabytes", "MB"),
+    ("gigabytes", "GB"),
+    ("terabytes", "TB"),
+    ("megahertz", "MHz"),
+    ("gigahertz", "GHz"),
+    ("milliseconds", "ms"),
+];
+
+fn small(w: &str) -> Option<u64> {
+    SMALL.iter().position(|s| *s == w).map(|v| v as u64)
+}
+
+fn tens(w: &str) -> Option<u64> {
+    TENS.iter().position(|t| *t == w).map(|i| (i as u64 + 2) * 10)
+}
+
+fn scale(w: &str) -> Option<u64> {
+    match w {
+        "thousand" => Some(1_000),
+        "million" => Some(1_000_000),
+        "billion" => Some(1_000_000_000),
+        _ => None,
+    }
+}
+
+fn ordinal(w: &str) -> Option<u64> {
+    if let Some(i) = SMALL_ORDINALS.iter().position(|o| *o == w) {
+        return Some(i as u64 + 1);
+    }
+    if let Some(i) = TENS_ORDINALS.iter().position(|o| *o == w) {
+        return Some((i as u64 + 2) * 10);
+    }
+    match w {
+        "hundredth" => Some(100),
+        "thousandth" => Some(1_000),
+        "millionth" => Some(1_000_000),
+        _ => None,
+    }
+}
+
+/// A single spoken digit, as in phone numbers and after a decimal point.
+fn digit(w: &str) -> Option<u64> {
+    match w {
+        "oh" => Some(0),
+        _ => small(w).filter(|d| *d < 10),
+    }
+}
+
+fn month(w: &str) -> Option<&'static str> {
+    MONTHS.iter().copied().find(|m| m.eq_ignore_ascii_case(w))
+}
+
+fn is_number_word(w: &str) -> bool {
+    small(w).is_some() || tens(w).is_some() || scale(w).is_some() || ordinal(w).is_some() || w == "hundred"
+}
+
+/// Number words plus "oh" as a digit, i.e. anything a grammar here might consume.
+pub(super) fn is_spoken_number(w: &str) -> bool {
+    is_number_word(w) || digit(w).is_some()
+}
+
+fn is_digits(w: &str) -> bool {
+    !w.is_empty() && w.len() <= 15 && w.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn word(tokens: &[Token], i: usize) -> &str {
+    tokens.get(i).map_or("", |t| t.word.as_str())
+}
+
+/// The word at `j` if it continues the phrase before it, i.e. no punctuation
+/// separates the two. "twenty, five" is two numbers, not 25.
+fn at(tokens: &[Token], j: usize) -> &str {
+    match (j.checked_sub(1).and_then(|p| tokens.get(p)), tokens.get(j)) {
+        (Some(prev), Some(token)) if prev.trail.is_empty() && token.lead.is_empty() => &token.word,
+        _ => "",
+    }
+}
+
+fn ends_sentence(tokens: &[Token], j: usize) -> bool {
+    tokens.get(j).is_some_and(|t| t.trail.contains(['.', '!', '?']))
+}
+
+/// 12345 → "12,345". Shorter numbers stay ungrouped so years read naturally.
+fn group(n: u64) -> String {
+    let digits = n.to_string();
+    if n < 10_000 {
+        return digits;
+    }
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn ordinal_suffix(n: u64) -> &'static str {
+    if (11..=13).contains(&(n % 100)) {
+        return "th";
+    }
+    match n % 10 {
+        1 => "st",
+        2 => "nd",
+        3 => "rd",
+        _ => "th",
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Last {
+    None,
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+    And,
+}
+
+/// A whole number starting at `i`, spelled out ("two thousand and five") or
+/// already in digits. Returns the value and the index after it.
+fn cardinal(tokens: &[Token], i: usize) -> Option<(u64, usize)> {
+    if is_digits(word(tokens, i)) {
+        return word(tokens, i).parse().ok().map(|n| (n, i + 1));
+    }
+
+    let (mut total, mut current, mut last_scale) = (0u64, 0u64, u64::MAX);
+    let mut last = Last::None;
+    let mut end = i;
+    let mut j = i;
+    loop {
+        let w = if j == i { word(tokens, i) } else { at(tokens, j) };
+        let next = at(tokens, j + 1);
+
+        if let Some(v) = small(w) {
+            let fits = match last {
+                Last::None | Last::Hundred | Last::Scale | Last::And => true,
+                Last::Tens => (1..10).contains(&v),
+                _ => false,
+            };
+            if !fits || (v == 0 && last != Last::None) {
+                break;
+            }
+            current += v;
+            last = if v < 10 { Last::Unit } else { Last::Teen };
+        } else if let Some(v) = tens(w) {
+            if !matches!(last, Last::None | Last::Hundred | Last::Scale | Last::And) {
+                break;
+            }
+            current += v;
+            last = Last::Tens;
+        } else if w == "hundred" {
+            if !matches!(last, Last::Unit | Last::Teen) || current >= 100 {
+                break;
+            }
+            current *= 100;
+            last = Last::Hundred;
+        } else if let Some(s) = scale(w) {
+            if matches!(last, Last::None | Last::And) || current == 0 || s >= last_scale {
+                break;
+            }
+            total += current * s;
+            current = 0;
+            last_scale = s;
+            last = Last::Scale;
+        } else if w == "and"
+            && matches!(last, Last::Hundred | Last::Scale)
+            && (small(next).is_some() || tens(next).is_some())
+        {
+            last = Last::And;
+            j += 1;
+            continue;
+        } else if w == "a" && last == Last::None && (next == "hundred" || scale(next).is_some()) {
+            current = 1;
+            last = Last::Unit;
+            j += 1;
+            continue;
+        } else {
+            break;
+        }
+        j += 1;
+        end = j;
+    }
+    (end > i).then_some((total + current, end))
+}
+
+/// "third", "twenty first", "one hundred and second".
+fn ordinal_number(tokens: &[Token], i: usize) -> Option<(u64, usize)> {
+    let (prefix, mut j) = cardinal(tokens, i)
+        .filter(|_| !is_digits(word(tokens, i)))
+        .unwrap_or((0, i));
+    let w = if j == i {
+        word(tokens, i)
+    } else {
+        if at(tokens, j) == "and" {
+            j += 1;
+        }
+        at(tokens, j)
+    };
+    let o = ordinal(w)?;
+
+    let value = if j == i {
+        o
+    } else if o >= 100 {
+        prefix.max(1) * o
+    } else if prefix % (if o < 10 { 10 } else { 100 }) == 0 {
+        prefix + o
+    } else {
+        return None;
+    };
+    Some((value, j + 1))
+}
+
+/// "three point one four" → "3.14".
+fn decimal(tokens: &[Token], i: usize) -> Option<(String, usize)> {
+    let (int, j) = cardinal(tokens, i)?;
+    if at(tokens, j) != "point" {
+        return None;
+    }
+    let mut k = j + 1;
+    let mut fraction = String::new();
+    while let Some(d) = digit(at(tokens, k)) {
+        fraction.push_str(&d.to_string());
+        k += 1;
+    }
+    (!fraction.is_empty()).then(|| (format!("{}.{}", group(int), fraction), k))
+}
+
+/// A decimal or whole number, written out, and whether it had a fraction.
+fn number(tokens: &[Token], i: usize) -> Option<(String, bool, usize)> {
+    if let Some((written, end)) = decimal(tokens, i) {
+        return Some((written, true, end));
+    }
+    cardinal(tokens, i).map(|(n, end)| (group(n), false, end))
+}
+
+fn meridiem(tokens: &[Token], j: usize) -> Option<(&'static str, usize)> {
+    match at(tokens, j).replace('.', "").as_str() {
+        "am" => Some(("AM", j + 1)),
+        "pm" => Some(("PM", j + 1)),
+        // Whisper sometimes writes "p. m."
+        "a" if tokens.get(j + 1).is_some_and(|t| t.word == "m") => Some(("AM", j + 2)),
+        "p" if tokens.get(j + 1).is_some_and(|t| t.word == "m") => Some(("PM", j + 2)),
+        _ => None,
+    }
+}
+
+/// "three thirty pm" → "3:30 PM", "seven o'clock" → "7:00". Without a.m./p.m.
+/// a bare "three thirty" only counts after a word like "at".
+fn time(tokens: &[Token], i: usize) -> Option<(String, usize)> {
+    let (hour, j) = cardinal(tokens, i).filter(|&(h, j)| (1..=12).contains(&h) && j == i + 1)?;
+
+    if at(tokens, j) == "o'clock" {
+        return Some(match meridiem(tokens, j + 1) {
+            Some((m, end)) => (format!("{}:00 {}", hour, m), end),
+            None => (format!("{}:00", hour), j + 1),
+        });
+    }
+
+    let minutes = match at(tokens, j) {
+        "" => None,
+        "oh" => digit(at(tokens, j + 1)).filter(|d| *d > 0).map(|d| (d, j + 2)),
+        _ => cardinal(tokens, j).filter(|&(m, _)| (10..60).contains(&m)),
+    };
+
+    match minutes {
+        Some((m, k)) => match meridiem(tokens, k) {
+            Some((mer, end)) => Some((format!("{}:{:02} {}", hour, m, mer), end)),
+            None => {
+                let cue = i.checked_sub(1).map_or("", |p| word(tokens, p));
+                matches!(cue, "at" | "by" | "until" | "till" | "around")
+                    .then(|| (format!("{}:{:02}", hour, m), k))
+            }
+        },
+        None => meridiem(tokens, j).map(|(mer, end)| (format!("{} {}", hour, mer), end)),
+    }
+}
+
+/// Spoken days have to be ordinals: "March third", not "March three", which is
+/// just as likely to be "in March three people...".
+fn day(tokens: &[Token], i: usize) -> Option<(u64, usize)> {
+    let day = if is_digits(word(tokens, i)) {
+        cardinal(tokens, i)
+    } else {
+        ordinal_number(tokens, i)
+    };
+    day.filter(|&(d, _)| (1..=31).contains(&d))
+}
+
+/// "twenty twenty five", "nineteen oh four", "two thousand and one", "2025".
+fn year(tokens: &[Token], i: usize) -> Option<(u64, usize)> {
+    let (first, j) = cardinal(tokens, i)?;
+    if (1000..=2999).contains(&first) {
+        return Some((first, j));
+    }
+    if !(10..100).contains(&first) || is_digits(word(tokens, i)) {
+        return None;
+    }
+    let (rest, end) = match at(tokens, j) {
+        "hundred" => (0, j + 1),
+        "oh" => digit(at(tokens, j + 1)).filter(|d| *d > 0).map(|d| (d, j + 2))?,
+        "" => return None,
+        _ => cardinal(tokens, j).filter(|&(r, _)| (10..100).contains(&r))?,
+    };
+    Some((first * 100 + rest, end))
+}
+
+/// "march third twenty twenty five" → "March 3, 2025", "the fourth of july" → "July 4".
+fn date(tokens: &[Token], i: usize) -> Option<(String, usize)> {
+    let (month, day, end) = if let Some(month) = month(word(tokens, i)) {
+        // "you may first..." isn't a date; Whisper capitalizes the month.
+        if at(tokens, i + 1).is_empty() || (month == "May" && !tokens[i].capitalized) {
+            return None;
+        }
+        let (day, end) = day(tokens, i + 1)?;
+        (month, day, end)
+    } else if word(tokens, i) == "the" && !at(tokens, i + 1).is_empty() {
+        let (day, end) = ordinal_number(tokens, i + 1).filter(|&(d, _)| (1..=31).contains(&d))?;
+        if at(tokens, end) != "of" {
+            return None;
+        }
+        let month = month(at(tokens, end + 1)).filter(|m| *m != "May" || tokens[end + 1].capitalized)?;
+        (month, day, end + 2)
+    } else {
+        return None;
+    };
+
+    // A comma may separate the year ("March 3rd, 2025"), a sentence end may not.
+    let year_follows = !ends_sentence(tokens, end - 1)
+        && tokens.get(end).is_some_and(|t| t.lead.is_empty());
+    match year_follows.then(|| year(tokens, end)).flatten() {
+        Some((year, year_end)) => Some((format!("{} {}, {}", month, day, year), year_end)),
+        None => Some((format!("{} {}", month, day), end)),
+    }
+}
+
+/// Seven, ten or eleven spoken digits ("five five five one two three four").
+/// Commas between groups are fine; "double"/"triple" repeat the next digit.
+fn phone(tokens: &[Token], i: usize) -> Option<(String, usize)> {
+    let mut digits = String::new();
+    let mut j = i;
+    while j < tokens.len() {
+        if j > i && (ends_sentence(tokens, j - 1) || !tokens[j].lead.is_empty()) {
+            break;
+        }
+        let (repeat, w) = match word(tokens, j) {
+            "double" => (2, word(tokens, j + 1)),
+            "triple" => (3, word(tokens, j + 1)),
+            w => (1, w),
+        };
+        let Some(d) = digit(w) else {
+            break;
+        };
+        for _ in 0..repeat {
+            digits.push_str(&d.to_string());
+        }
+        j += if repeat > 1 { 2 } else { 1 };
+    }
+
+    let formatted = match digits.len() {
+        7 => format!("{}-{}", &digits[..3], &digits[3..]),
+        10 => format!("({}) {}-{}", &digits[..3], &digits[3..6], &digits[6..]),
+        11 if digits.starts_with('1') => {
+            format!("+1 ({}) {}-{}", &digits[1..4], &digits[4..7], &digits[7..])
+        }
+        _ => return None,
+    };
+    Some((formatted, j))
+}
+
+/// "twenty five dollars and fifty cents" → "$25.50", "fifty cents" → "50¢".
+fn currency(tokens: &[Token], i: usize) -> Option<(String, usize)> {
+    let (amount, is_decimal, j) = number(tokens, i)?;
+    let w = at(tokens, j);
+
+    if matches!(w, "cent" | "cents") && !is_decimal {
+        return Some((format!("{}¢", amount), j + 1));
+    }
+    let symbol = CURRENCIES.iter().find(|(name, _)| *name == w)?.1;
+    let amount = match amount.split_once('.') {
+        Some((_, fraction)) if fraction.len() == 1 => format!("{}0", amount),
+        _ => amount,
+    };
+
+    let k = if at(tokens, j + 1) == "and" { j + 2 } else { j + 1 };
+    if !is_decimal && !at(tokens, k).is_empty() {
+        if let Some((cents, end)) = cardinal(tokens, k).filter(|&(c, _)| c < 100) {
+            if matches!(at(tokens, end), "cent" | "cents") {
+                return Some((format!("{}{}.{:02}", symbol, amount, cents), end + 1));
+            }
+        }
+    }
+    Some((format!("{}{}", symbol, amount), j + 1))
+}
+
+fn percentage(tokens: &[Token], i: usize) -> Option<(String, usize)> {
+    let (amount, _, j) = number(tokens, i)?;
+    match at(tokens, j) {
+        "percent" => Some((format!("{}%", amount), j + 1)),
+        "per" if at(tokens, j + 1) == "cent" => Some((format!("{}%", amount), j + 2)),
+        _ => None,
+    }
+}
+
+fn unit(tokens: &[Token], i: usize) -> Option<(String, usize)> {
+    let (amount, _, j) = number(tokens, i)?;
+    UNITS.iter().find_map(|(name, symbol)| {
+        let words: Vec<&str> = name.split(' ').collect();
+        let matches = words.iter().enumerate().all(|(k, w)| at(tokens, j + k) == *w);
+        if !matches {
+            return None;
+        }
+        let written = if symbol.starts_with('°') {
+            format!("{}{}", amount, symbol)
+        } else {
+            format!("{} {}", amount, symbol)
+        };
+        Some((written, j + words.len()))
+    })
+}
+
+/// The written form of whatever starts at `i`, tried from the most specific
+/// category to the least. Numbers under ten stay spelled out unless they're
+/// part of something bigger, as most style guides ask.
+fn rewrite_at(tokens: &[Token], i: usize, settings: &ItnSettings) -> Option<(String, usize)> {
+    let rules: [(bool, fn(&[Token], usize) -> Option<(String, usize)>); 6] = [
+        (settings.times, time),
+        (settings.dates, date),
+        (settings.phone_numbers, phone),
+        (settings.currency, currency),
+        (settings.percentages, percentage),
+        (settings.units, unit),
+    ];
+    if let Some(found) = rules
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .find_map(|(_, rule)| rule(tokens, i))
+    {
+        return Some(found);
+    }
+
+    // A year on its own ("in nineteen ninety nine") isn't a valid cardinal.
+    if settings.dates && !is_digits(word(tokens, i)) {
+        if let Some((year, end)) = year(tokens, i).filter(|&(y, end)| end > i + 1 && (1900..2100).contains(&y)) {
+            return Some((year.to_string(), end));
+        }
+    }
+    if settings.ordinals {
+        if let Some((n, end)) = ordinal_number(tokens, i).filter(|&(n, _)| n >= 10) {
+            return Some((format!("{}{}", group(n), ordinal_suffix(n)), end));
+        }
+    }
+    if settings.decimals {
+        if let Some(found) = decimal(tokens, i) {
+            return Some(found);
+        }
+    }
+    if settings.cardinals && !is_digits(word(tokens, i)) {
+        // Leave "twenty first" alone rather than half-converting it.
+        if let Some((n, end)) = cardinal(tokens, i)
+            .filter(|&(n, end)| n >= 10 && ordinal(at(tokens, end)).is_none())
+        {
+            return Some((group(n), end));
+        }
+    }
+    None
+}
+
+pub(super) fn normalize(text: &str, settings: &ItnSettings) -> String {
+    let tokens = tokenize(text, is_number_word);
+    let mut out = Rewriter::new(text);
+    let mut i = 0;
+    while i < tokens.len() {
+        match rewrite_at(&tokens, i, settings) {
+            Some((written, end)) => {
+                out.replace(&tokens[i..end], &written);
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    out.finish()
+}
@@ -0,0 +1,167 @@
+//! Inverse text normalization: rewrites spoken forms like "twenty five dollars"
+//! as written ones like "$25". Each language has its own grammar module; text
+//! in languages without one passes through untouched.
+
+use serde::{Deserialize, Serialize};
+
+mod en;
+
+fn default_true() -> bool {
+    true
+}
+
+/// Categories can be switched off one by one, e.g. to keep small numbers
+/// spelled out while still formatting times.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct ItnSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub cardinals: bool,
+    #[serde(default = "default_true")]
+    pub ordinals: bool,
+    #[serde(default = "default_true")]
+    pub decimals: bool,
+    #[serde(default = "default_true")]
+    pub percentages: bool,
+    #[serde(default = "default_true")]
+    pub currency: bool,
+    #[serde(default = "default_true")]
+    pub dates: bool,
+    #[serde(default = "default_true")]
+    pub times: bool,
+    #[serde(default = "default_true")]
+    pub phone_numbers: bool,
+    #[serde(default = "default_true")]
+    pub units: bool,
+}
+
+impl Default for ItnSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cardinals: true,
+            ordinals: true,
+            decimals: true,
+            percentages: true,
+            currency: true,
+            dates: true,
+            times: true,
+            phone_numbers: true,
+            units: true,
+        }
+    }
+}
+
+pub fn normalize(text: &str, language: &str, settings: &ItnSettings) -> String {
+    if !settings.enabled {
+        return text.to_string();
+    }
+    match language {
+        "en" => en::normalize(text, settings),
+        _ => text.to_string(),
+    }
+}
+
+/// Whether the lowercased `word` can be part of a spoken number in `language`.
+/// Earlier stages use this to leave repeats like "five five five" for ITN.
+pub fn is_number_word(word: &str, language: &str) -> bool {
+    match language {
+        "en" => en::is_spoken_number(word),
+        _ => false,
+    }
+}
+
+/// A word of the input with the punctuation around it split off. `start..end`
+/// covers the punctuation too, so a rewrite can replace the whole thing.
+struct Token {
+    start: usize,
+    end: usize,
+    word: String, // Lowercased
+    capitalized: bool,
+    lead: String,
+    trail: String,
+}
+
+/// Splits `text` into tokens. Hyphenated words are split further when every
+/// part passes `split_part` ("twenty-five"), so grammars see the parts.
+fn tokenize(text: &str, split_part: fn(&str) -> bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for piece in text.split_whitespace() {
+        let start = piece.as_ptr() as usize - text.as_ptr() as usize;
+        let end = start + piece.len();
+        let is_core = |c: char| c.is_alphanumeric() || c == '\'';
+        let Some(core_start) = piece.find(is_core) else {
+            continue;
+        };
+        let core_end = piece
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| is_core(c))
+            .map_or(piece.len(), |(i, c)| i + c.len_utf8());
+        let core = &piece[core_start..core_end];
+        let lead = piece[..core_start].to_string();
+        let trail = piece[core_end..].to_string();
+
+        let parts: Vec<&str> = core.split('-').collect();
+        if parts.len() > 1 && parts.iter().all(|p| !p.is_empty() && split_part(&p.to_lowercase())) {
+            let mut offset = start + core_start;
+            for (i, part) in parts.iter().enumerate() {
+                let last = i == parts.len() - 1;
+                tokens.push(Token {
+                    start: if i == 0 { start } else { offset },
+                    end: if last { end } else { offset + part.len() },
+                    word: part.to_lowercase(),
+                    capitalized: part.starts_with(char::is_uppercase),
+                    lead: if i == 0 { lead.clone() } else { String::new() },
+                    trail: if last { trail.clone() } else { String::new() },
+                });
+                offset += part.len() + 1;
+            }
+        } else {
+            tokens.push(Token {
+                start,
+                end,
+                word: core.to_lowercase(),
+                capitalized: core.starts_with(char::is_uppercase),
+                lead,
+                trail,
+            });
+        }
+    }
+    tokens
+}
+
+/// Builds the output by swapping runs of tokens for their written form and
+/// copying everything else byte for byte.
+struct Rewriter<'a> {
+    text: &'a str,
+    out: String,
+    copied_to: usize,
+}
+
+impl<'a> Rewriter<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            out: String::with_capacity(text.len()),
+            copied_to: 0,
+        }
+    }
+
+    fn replace(&mut self, tokens: &[Token], written: &str) {
+        let (Some(first), Some(last)) = (tokens.first(), tokens.last()) else {
+            return;
+        };
+        self.out.push_str(&self.text[self.copied_to..first.start]);
+        self.out.push_str(&first.lead);
+        self.out.push_str(written);
+        self.out.push_str(&last.trail);
+        self.copied_to = last.end;
+    }
+
+    fn finish(mut self) -> String {
+        self.out.push_str(&self.text[self.copied_to..]);
+        self.out
+    }
+}
@@ -4,9 +4,28 @@ mod platform;
 mod tray;
 mod window;
 mod whisper;
-mod groq;
+mod openai_compat;
 mod transcripts;
+mod backend;
 mod sound;
+mod metrics;
+mod benchmark;
+mod pipeline;
+mod secrets;
+mod usage;
+mod net;
+mod model_bundle;
+mod postprocess;
+mod formatting;
+mod editing;
+mod snippets;
+mod dictionary;
+mod corrections;
+mod itn;
+mod profiles;
+mod rewrite;
+mod command_mode;
+pub mod eval;
 
 mod fn_key_listener;
 mod fn_key_monitor;
@@ -22,6 +41,7 @@ use tauri::{State, Manager, Listener, RunEvent};
 use tauri_plugin_store::StoreExt;
 use tauri_specta::{collect_commands, collect_events, Builder, Event};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tauri_plugin_notification::NotificationExt;
 
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type, tauri_specta::Event)]
 pub struct TranscriptionProgress {
@@ -64,6 +84,13 @@ pub struct RecordingStatsUpdated {
     pub session_wpm: f32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type, tauri_specta::Event)]
+pub struct TranscriptRefined {
+    pub transcript_id: String,
+    pub draft_text: String,
+    pub text: String,
+}
+
 #[derive(Serialize, Deserialize, specta::Type)]
 pub struct AppSettings {
     pub selected_microphone: Option<String>,
@@ -76,6 +103,48 @@ pub struct AppSettings {
     pub current_session_start: Option<i64>,
     pub whisper_language: Option<String>,
     pub whisper_temperature: Option<f32>,
+    #[serde(default)]
+    pub two_pass_enabled: bool,
+    #[serde(default)]
+    pub draft_model: Option<String>,
+    #[serde(default)]
+    pub context_carryover_enabled: bool,
+    #[serde(default)]
+    pub context_carryover_chars: Option<u32>,
+    #[serde(default)]
+    pub context_carryover_window_secs: Option<u32>,
+    #[serde(default)]
+    pub context_reset_at: Option<i64>,
+    #[serde(default)]
+    pub active_backend: Option<String>,
+    #[serde(default)]
+    pub cloud_providers: Vec<openai_compat::ProviderConfig>,
+    #[serde(default)]
+    pub usage_limits: usage::UsageLimits,
+    #[serde(default)]
+    pub privacy_mode: bool, // Blocks every network request, cloud and downloads alike
+    #[serde(default)]
+    pub network: net::NetworkSettings,
+    #[serde(default)]
+    pub post_processing: postprocess::PostProcessSettings,
+    #[serde(default)]
+    pub formatting: formatting::FormattingSettings,
+    #[serde(default)]
+    pub editing: editing::EditingSettings,
+    #[serde(default)]
+    pub vocabulary: Vec<String>, // Names and terms passed to the model as a prompt
+    #[serde(default)]
+    pub corrections: corrections::CorrectionSettings,
+    #[serde(default)]
+    pub itn: itn::ItnSettings,
+    #[serde(default)]
+    pub profiles: Vec<profiles::Profile>,
+    #[serde(default)]
+    pub profile_override: Option<String>, // Used for every recording instead of matching the focused app
+    #[serde(default)]
+    pub rewrite: rewrite::RewriteSettings,
+    #[serde(default)]
+    pub command_hotkey: Option<String>, // Held to speak an instruction for the selected text
 }
 
 pub struct BubbleShowTaskState {
@@ -93,6 +162,23 @@ impl AppSettings {
         }
     }
 
+    /// Reads the settings straight from the store file in `data_dir`, for
+    /// headless runs that don't start the app.
+    pub fn load_from_dir(data_dir: &std::path::Path) -> Result<Option<Self>, String> {
+        let path = data_dir.join("settings.json");
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let mut store: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&data).map_err(|e| e.to_string())?;
+        store
+            .remove("app_settings")
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| e.to_string())
+    }
+
     pub fn set<R: tauri::Runtime>(app: &tauri::AppHandle<R>, settings: &Self) -> Result<(), String> {
         let store = app.store("settings.json").map_err(|e| e.to_string())?;
         let value = serde_json::to_value(settings).map_err(|e| e.to_string())?;
@@ -118,6 +204,27 @@ impl Default for AppSettings {
             current_session_start: None,
             whisper_language: Some("en".to_string()),
             whisper_temperature: Some(0.0),
+            two_pass_enabled: false,
+            draft_model: None,
+            context_carryover_enabled: false,
+            context_carryover_chars: Some(200),
+            context_carryover_window_secs: Some(120),
+            context_reset_at: None,
+            active_backend: None,
+            cloud_providers: Vec::new(),
+            usage_limits: usage::UsageLimits::default(),
+            privacy_mode: false,
+            network: net::NetworkSettings::default(),
+            post_processing: postprocess::PostProcessSettings::default(),
+            formatting: formatting::FormattingSettings::default(),
+            editing: editing::EditingSettings::default(),
+            vocabulary: Vec::new(),
+            corrections: corrections::CorrectionSettings::default(),
+            itn: itn::ItnSettings::default(),
+            profiles: Vec::new(),
+            profile_override: None,
+            rewrite: rewrite::RewriteSettings::default(),
+            command_hotkey: None,
         }
     }
 }
@@ -175,6 +282,7 @@ async fn start_recording(
     app: tauri::AppHandle,
     audio_manager: State<'_, Arc<AudioManager>>
 ) -> Result<(), String> {
+    begin_dictation(&app, None);
     let start_time = chrono::Utc::now().timestamp_millis();
     
     let mut settings = AppSettings::get_or_default(&app);
@@ -191,22 +299,17 @@ async fn start_recording(
     audio_manager.start_recording().await
 }
 
-#[tauri::command]
-#[specta::specta]
-async fn stop_recording(
-    app: tauri::AppHandle,
-    audio_manager: State<'_, Arc<AudioManager>>,
-    whisper_model: State<'_, Arc<Mutex<WhisperModel>>>,
+/// Everything after the microphone stops: transcribe, clean up, update the
+/// stats and store the transcript. Returns the text for the caller to insert,
+/// or an empty string when there is nothing to insert.
+async fn finish_recording(
+    app: &tauri::AppHandle,
+    audio: (Vec<f32>, u32, f32),
+    dictation: profiles::DictationContext,
 ) -> Result<String, String> {
-    let start_time = std::time::Instant::now();
-    
-    let (audio_data, sample_rate, peak_level) = audio_manager.stop_recording().await?;
-    let stop_recording_time = start_time.elapsed();
-    println!("⏱️ Stop recording took: {:?}", stop_recording_time);
+    let (audio_data, sample_rate, peak_level) = audio;
     
-    const SILENCE_THRESHOLD: f32 = 0.01;
-    
-    if audio_data.is_empty() || peak_level < SILENCE_THRESHOLD {
+    if !pipeline::has_meaningful_audio(&audio_data, peak_level) {
         println!("🔇 Skipping transcription - no meaningful audio detected (peak level: {:.4})", peak_level);
         return Ok(String::new());
     }
@@ -214,43 +317,53 @@ async fn stop_recording(
     let audio_duration_secs = audio_data.len() as f32 / sample_rate as f32;
     println!("🎙️ Audio duration: {:.2}s ({} samples at {} Hz)", audio_duration_secs, audio_data.len(), sample_rate);
     
+    let profile = dictation.profile.as_ref();
+    
     let transcribe_start = std::time::Instant::now();
-    let text = {
-        let store = app.store("settings.json").map_err(|e| e.to_string())?;
-        let key_opt = match store.get("groq_api_key") {
-            Some(val) => val.as_str().map(|s| s.to_string()),
-            None => None,
-        };
-        match key_opt {
-            Some(key) => {
-                println!("☁️ Using Groq for transcription");
-                let settings = AppSettings::get_or_default(&app);
-                groq::transcribe_with_groq(&audio_data, sample_rate, settings.whisper_language, &key).await?
-            }
-            None => {
-                println!("🧠 Using local Whisper for transcription");
-                let model = whisper_model.lock().unwrap();
-                let cfg = whisper::WhisperRuntimeConfig {
-                    language: AppSettings::get_or_default(&app).whisper_language,
-                    temperature: AppSettings::get_or_default(&app).whisper_temperature,
-                };
-                model.transcribe_with_config(&audio_data, sample_rate, &cfg)?
-            }
-        }
-    };
+    let cfg = whisper_runtime_config(app, profile);
+    let backend = app
+        .state::<backend::BackendRegistry>()
+        .resolve(app, profile.and_then(|p| p.backend_id.as_deref()));
+    println!("🧠 Using {} for transcription", backend.name());
+    let backend::BackendOutput {
+        output,
+        model_used,
+        refinement,
+    } = backend::transcribe_with_fallback(app, backend, &audio_data, sample_rate, &cfg).await?;
     let transcribe_time = transcribe_start.elapsed();
     println!("⏱️ Transcription took: {:?} (RTF: {:.2}x)", transcribe_time, transcribe_time.as_secs_f32() / audio_duration_secs);
     
-    let trimmed_text = text.trim();
-    if trimmed_text.chars().all(|c| c.is_whitespace() || c.is_ascii_punctuation()) {
-        println!("🔇 Skipping transcription - only contains punctuation/whitespace: '{}'", trimmed_text);
+    let Some(whisper::TranscriptionOutput { text, segments, language }) = pipeline::clean_output(output) else {
+        return Ok(String::new());
+    };
+    
+    if run_editing_command(app, &text) {
+        return Ok(String::new());
+    }
+    
+    let raw_text = text;
+    let text = pipeline::process_text(app, &raw_text, language.as_deref(), profile);
+    if text.is_empty() {
+        println!("🔇 Skipping transcription - nothing left after post-processing");
         return Ok(String::new());
     }
     
+    let (text, pre_rewrite_text) = match rewrite_transcript(app, &text, &dictation).await {
+        Some(rewritten) => (rewritten, Some(text)),
+        None => (text, None),
+    };
+    // Refining would bring back the text from before the rewrite.
+    let refinement = refinement.filter(|_| pre_rewrite_text.is_none());
+    
+    TranscriptionProgress {
+        text: text.clone(),
+        is_final: refinement.is_none(),
+    }.emit(app).ok();
+    
     let words = text.split_whitespace().count() as u32;
-    if words > 0 || audio_data.len() > 0 {
+    if words > 0 || !audio_data.is_empty() {
         let end_time = chrono::Utc::now().timestamp_millis();
-        let mut settings = AppSettings::get_or_default(&app);
+        let mut settings = AppSettings::get_or_default(app);
         
         let session_duration_ms = if let Some(start) = settings.current_session_start {
             (end_time - start) as f64
@@ -276,7 +389,7 @@ async fn stop_recording(
         
         settings.current_session_start = None;
         
-        AppSettings::set(&app, &settings)?;
+        AppSettings::set(app, &settings)?;
         
         RecordingStatsUpdated {
             total_words: settings.word_count,
@@ -285,17 +398,11 @@ async fn stop_recording(
             session_words: words,
             session_time_ms: session_duration_ms,
             session_wpm,
-        }.emit(&app).ok();
+        }.emit(app).ok();
         
-        WordCountUpdated { count: settings.word_count }.emit(&app).ok();
+        WordCountUpdated { count: settings.word_count }.emit(app).ok();
         
         if !text.is_empty() {
-            let store = app.store("settings.json").map_err(|e| e.to_string())?;
-            let model_used = if store.get("groq_api_key").is_some() {
-                Some("groq/whisper-large-v3".to_string())
-            } else {
-                settings.selected_model.clone()
-            };
             let transcript = Transcript {
                 id: uuid::Uuid::new_v4().to_string(),
                 text: text.clone(),
@@ -304,20 +411,240 @@ async fn stop_recording(
                 word_count: words,
                 wpm: session_wpm,
                 model_used,
+                draft_text: None,
+                segments,
+                language,
+                raw_text: Some(raw_text),
+                original_text: None,
+                edited_at: None,
+                profile_id: profile.map(|p| p.id.clone()),
+                source_app: dictation.source_app.clone(),
+                pre_rewrite_text,
             };
+            let transcript_id = transcript.id.clone();
             
-            let mut store = TranscriptStore::load(&app).unwrap_or_default();
+            let mut store = TranscriptStore::load(app).unwrap_or_default();
             store.add_transcript(transcript);
-            let _ = store.save(&app);
+            let _ = store.save(app);
+            
+            if let Some(accurate) = refinement {
+                spawn_refinement(app.clone(), accurate, audio_data, sample_rate, cfg, profile.cloned(), transcript_id, text.clone());
+            }
         }
     }
     
-    let total_time = start_time.elapsed();
-    println!("⏱️ Total stop_recording command took: {:?}", total_time);
+    Ok(text)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn stop_recording(
+    app: tauri::AppHandle,
+    audio_manager: State<'_, Arc<AudioManager>>,
+) -> Result<String, String> {
+    let start_time = std::time::Instant::now();
+    
+    let audio = audio_manager.stop_recording().await?;
+    println!("⏱️ Stop recording took: {:?}", start_time.elapsed());
+    
+    let dictation = app.state::<profiles::ProfileState>().current();
+    let text = finish_recording(&app, audio, dictation).await?;
+    
+    println!("⏱️ Total stop_recording command took: {:?}", start_time.elapsed());
     
     Ok(text)
 }
 
+/// Resolves the dictation profile as recording starts, from the override or
+/// the app that has focus. Our own windows don't count as a source app.
+fn begin_dictation(app: &tauri::AppHandle, hotkey_prompt_id: Option<String>) {
+    let settings = AppSettings::get_or_default(app);
+    let state = app.state::<profiles::ProfileState>();
+    let source_app = state
+        .focused_app()
+        .filter(|a| a.bundle_id.as_deref() != Some(app.config().identifier.as_str()));
+    let context = state.begin(
+        &settings.profiles,
+        settings.profile_override.as_deref(),
+        source_app,
+        hotkey_prompt_id,
+    );
+    profiles::ProfileSelected {
+        profile_id: context.profile.as_ref().map(|p| p.id.clone()),
+        profile_name: context.profile.as_ref().map(|p| p.name.clone()),
+        source_app: context.source_app.map(|a| a.name),
+    }.emit(app).ok();
+}
+
+/// Runs the LLM rewrite stage if a prompt applies, from the hotkey, the
+/// profile or the default, in that order. The reply streams into the bubble.
+/// Returns `None` when nothing was rewritten, failures included, so the
+/// dictation goes in as transcribed.
+async fn rewrite_transcript(
+    app: &tauri::AppHandle,
+    text: &str,
+    dictation: &profiles::DictationContext,
+) -> Option<String> {
+    let settings = AppSettings::get_or_default(app).rewrite;
+    if !settings.enabled {
+        return None;
+    }
+    let prompt_id = dictation
+        .hotkey_prompt_id
+        .as_deref()
+        .or(dictation.profile.as_ref().and_then(|p| p.rewrite_prompt_id.as_deref()))
+        .or(settings.default_prompt_id.as_deref())?;
+    let Some(prompt) = settings.prompt(prompt_id) else {
+        eprintln!("Rewrite prompt {} not found", prompt_id);
+        return None;
+    };
+    
+    println!("🪄 Rewriting with \"{}\"", prompt.name);
+    let rewrite_start = std::time::Instant::now();
+    match stream_completion(app, &settings.endpoint, &prompt.system_prompt, text).await {
+        Ok(rewritten) => {
+            println!("⏱️ Rewrite took: {:?}", rewrite_start.elapsed());
+            Some(rewritten)
+        }
+        Err(e) => {
+            eprintln!("Rewrite failed, keeping the transcription as is: {}", e);
+            None
+        }
+    }
+}
+
+/// Sends `user` to the rewrite endpoint, streaming the reply into the bubble.
+async fn stream_completion(
+    app: &tauri::AppHandle,
+    endpoint: &rewrite::RewriteEndpoint,
+    system: &str,
+    user: &str,
+) -> Result<String, String> {
+    let _ = window::resize_bubble_window(app, 340.0, 70.0);
+    let _ = window::show_bubble_window(app);
+    let result = rewrite::complete(app, endpoint, system, user, |partial| {
+        TranscriptionProgress {
+            text: partial.to_string(),
+            is_final: false,
+        }.emit(app).ok();
+    }).await;
+    let _ = window::hide_bubble_window(app);
+    let _ = window::resize_bubble_window(app, 70.0, 35.0);
+    result
+}
+
+/// Carries out `utterance` instead of inserting it when the whole thing is an
+/// editing command like "scratch that". Returns whether it was one.
+fn run_editing_command(app: &tauri::AppHandle, utterance: &str) -> bool {
+    let settings = AppSettings::get_or_default(app).editing;
+    if !settings.enabled {
+        return false;
+    }
+    let Some(command) = editing::parse_command(utterance) else {
+        return false;
+    };
+    if let Err(e) = editing::run(&app.state::<editing::InsertionHistory>(), command, &settings) {
+        eprintln!("Failed to run editing command: {}", e);
+    }
+    true
+}
+
+pub(crate) fn whisper_runtime_config(app: &tauri::AppHandle, profile: Option<&profiles::Profile>) -> whisper::WhisperRuntimeConfig {
+    let settings = AppSettings::get_or_default(app);
+    let carryover = if settings.context_carryover_enabled {
+        let window_ms = settings.context_carryover_window_secs.unwrap_or(120) as f64 * 1000.0;
+        let mut since = chrono::Utc::now().timestamp_millis() as f64 - window_ms;
+        if let Some(reset_at) = settings.context_reset_at {
+            since = since.max(reset_at as f64);
+        }
+        TranscriptStore::load(app)
+            .unwrap_or_default()
+            .recent_context(since, settings.context_carryover_chars.unwrap_or(200) as usize)
+    } else {
+        None
+    };
+    runtime_config_from_settings(&settings, profile, carryover)
+}
+
+/// Config from settings alone; `carryover` is text from recent dictations to
+/// prime the decoder with, if any.
+pub(crate) fn runtime_config_from_settings(
+    settings: &AppSettings,
+    profile: Option<&profiles::Profile>,
+    carryover: Option<String>,
+) -> whisper::WhisperRuntimeConfig {
+    // Carryover goes last so it runs straight into the new audio.
+    let mut vocabulary = settings.vocabulary.clone();
+    if let Some(profile) = profile {
+        vocabulary.extend(profile.vocabulary.iter().filter(|t| !settings.vocabulary.contains(t)).cloned());
+    }
+    let vocabulary = (!vocabulary.is_empty()).then(|| vocabulary.join(", "));
+    let initial_prompt = match (vocabulary, carryover) {
+        (Some(vocabulary), Some(carryover)) => Some(format!("{}. {}", vocabulary, carryover)),
+        (vocabulary, carryover) => vocabulary.or(carryover),
+    };
+    
+    whisper::WhisperRuntimeConfig {
+        language: profile
+            .and_then(|p| p.language.clone())
+            .or(settings.whisper_language.clone()),
+        temperature: settings.whisper_temperature,
+        initial_prompt,
+        local_model: profile.and_then(|p| p.local_model.clone()),
+        cloud_model: profile.and_then(|p| p.cloud_model.clone()),
+    }
+}
+
+/// Re-decodes a drafted recording with the accurate model in the background and
+/// replaces the saved transcript if the result differs.
+fn spawn_refinement(
+    app: tauri::AppHandle,
+    accurate: whisper::LoadedModel,
+    audio_data: Vec<f32>,
+    sample_rate: u32,
+    cfg: whisper::WhisperRuntimeConfig,
+    profile: Option<profiles::Profile>,
+    transcript_id: String,
+    draft_text: String,
+) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let refine_start = std::time::Instant::now();
+        let output = match accurate.transcribe_detailed(&audio_data, sample_rate, &cfg) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Failed to refine draft: {}", e);
+                return;
+            }
+        };
+        println!("⏱️ Refinement with {} took: {:?}", accurate.model_id, refine_start.elapsed());
+        let output = pipeline::drop_low_confidence_segments(output);
+        
+        let raw_text = pipeline::finalize_text(&output.text).unwrap_or_default();
+        // The draft already counted its replacement hits.
+        let (text, _) = pipeline::TextStages::load(&app).apply(&raw_text, output.language.as_deref(), profile.as_ref());
+        if text.is_empty() || text == draft_text {
+            println!("✅ Draft matches refined transcription");
+            return;
+        }
+        
+        let mut store = TranscriptStore::load(&app).unwrap_or_default();
+        if store.apply_refinement(&transcript_id, raw_text, text.clone(), output.segments).is_ok() {
+            let _ = store.save(&app);
+        }
+        
+        TranscriptRefined {
+            transcript_id,
+            draft_text,
+            text: text.clone(),
+        }.emit(&app).ok();
+        
+        TranscriptionProgress {
+            text,
+            is_final: true,
+        }.emit(&app).ok();
+    });
+}
+
 #[tauri::command]
 #[specta::specta]
 fn get_whisper_settings(app: tauri::AppHandle) -> Result<(Option<String>, Option<f32>), String> {
@@ -333,132 +660,51 @@ fn set_whisper_settings(app: tauri::AppHandle, language: Option<String>, tempera
     s.whisper_temperature = temperature;
     AppSettings::set(&app, &s)
 }
+#[tauri::command]
+#[specta::specta]
+fn get_context_carryover_settings(app: tauri::AppHandle) -> Result<(bool, Option<u32>, Option<u32>), String> {
+    let s = AppSettings::get_or_default(&app);
+    Ok((s.context_carryover_enabled, s.context_carryover_chars, s.context_carryover_window_secs))
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_context_carryover_settings(
+    app: tauri::AppHandle,
+    enabled: bool,
+    max_chars: Option<u32>,
+    window_secs: Option<u32>,
+) -> Result<(), String> {
+    let mut s = AppSettings::get_or_default(&app);
+    s.context_carryover_enabled = enabled;
+    s.context_carryover_chars = max_chars;
+    s.context_carryover_window_secs = window_secs;
+    AppSettings::set(&app, &s)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn reset_dictation_context(app: tauri::AppHandle) -> Result<(), String> {
+    let mut s = AppSettings::get_or_default(&app);
+    s.context_reset_at = Some(chrono::Utc::now().timestamp_millis());
+    AppSettings::set(&app, &s)
+}
+
 #[tauri::command]
 #[specta::specta]
 async fn stop_recording_manual(
     app: tauri::AppHandle,
     audio_manager: State<'_, Arc<AudioManager>>,
-    whisper_model: State<'_, Arc<Mutex<WhisperModel>>>,
 ) -> Result<String, String> {
     let start_time = std::time::Instant::now();
     
-    let (audio_data, sample_rate, peak_level) = audio_manager.stop_recording().await?;
-    let stop_recording_time = start_time.elapsed();
-    println!("⏱️ Stop recording took: {:?}", stop_recording_time);
-    
-    const SILENCE_THRESHOLD: f32 = 0.01;
-    
-    if audio_data.is_empty() || peak_level < SILENCE_THRESHOLD {
-        println!("🔇 Skipping transcription - no meaningful audio detected (peak level: {:.4})", peak_level);
-        return Ok(String::new());
-    }
-    
-    let audio_duration_secs = audio_data.len() as f32 / sample_rate as f32;
-    println!("🎙️ Audio duration: {:.2}s ({} samples at {} Hz)", audio_duration_secs, audio_data.len(), sample_rate);
-    
-    let transcribe_start = std::time::Instant::now();
-    let text = {
-        let store = app.store("settings.json").map_err(|e| e.to_string())?;
-        let key_opt = match store.get("groq_api_key") {
-            Some(val) => val.as_str().map(|s| s.to_string()),
-            None => None,
-        };
-        match key_opt {
-            Some(key) => {
-                println!("☁️ Using Groq for transcription");
-                let settings = AppSettings::get_or_default(&app);
-                groq::transcribe_with_groq(&audio_data, sample_rate, settings.whisper_language, &key).await?
-            }
-            None => {
-                println!("🧠 Using local Whisper for transcription");
-                let model = whisper_model.lock().unwrap();
-                let cfg = whisper::WhisperRuntimeConfig {
-                    language: AppSettings::get_or_default(&app).whisper_language,
-                    temperature: AppSettings::get_or_default(&app).whisper_temperature,
-                };
-                model.transcribe_with_config(&audio_data, sample_rate, &cfg)?
-            }
-        }
-    };
-    let transcribe_time = transcribe_start.elapsed();
-    println!("⏱️ Transcription took: {:?} (RTF: {:.2}x)", transcribe_time, transcribe_time.as_secs_f32() / audio_duration_secs);
-    
-    let trimmed_text = text.trim();
-    if trimmed_text.is_empty() || trimmed_text.chars().all(|c| c.is_ascii_punctuation() || c.is_whitespace()) {
-        println!("🔇 Skipping - transcription contains no meaningful text");
-        return Ok(String::new());
-    }
-    
-    let word_stats_start = std::time::Instant::now();
-    
-    let words = text.split_whitespace().count() as u32;
-    
-    if words > 0 {
-        let mut settings = AppSettings::get_or_default(&app);
-        let session_duration_ms = if let Some(start_time) = settings.current_session_start {
-            (chrono::Utc::now().timestamp_millis() - start_time) as f64
-        } else {
-            0.0
-        };
-        
-        let session_wpm = if session_duration_ms > 0.0 {
-            (words as f64 / (session_duration_ms / 60000.0)) as f32
-        } else {
-            0.0
-        };
-        
-        settings.word_count += words;
-        settings.total_recording_time_ms += session_duration_ms;
-        settings.last_recording_time = Some(chrono::Utc::now().timestamp_millis());
-        settings.current_session_start = None;
-        
-        let overall_wpm = if settings.total_recording_time_ms > 0.0 {
-            (settings.word_count as f64 / (settings.total_recording_time_ms / 60000.0)) as f32
-        } else {
-            0.0
-        };
-        
-        AppSettings::set(&app, &settings)?;
-        
-        let _ = WordCountUpdated { count: settings.word_count }.emit(&app);
-        
-        let _ = RecordingStatsUpdated {
-            total_words: settings.word_count,
-            total_time_ms: settings.total_recording_time_ms,
-            overall_wpm,
-            session_words: words,
-            session_time_ms: session_duration_ms,
-            session_wpm,
-        }.emit(&app);
-        
-        if words > 0 {
-            let store = app.store("settings.json").map_err(|e| e.to_string())?;
-            let model_used = if store.get("groq_api_key").is_some() {
-                Some("groq/whisper-large-v3".to_string())
-            } else {
-                settings.selected_model.clone()
-            };
-            let transcript = Transcript {
-                id: uuid::Uuid::new_v4().to_string(),
-                text: text.clone(),
-                timestamp: chrono::Utc::now().timestamp_millis() as f64,
-                duration_ms: session_duration_ms,
-                word_count: words,
-                wpm: session_wpm,
-                model_used,
-            };
-            
-            let mut store = TranscriptStore::load(&app).unwrap_or_default();
-            store.add_transcript(transcript);
-            let _ = store.save(&app);
-        }
-    }
+    let audio = audio_manager.stop_recording().await?;
+    println!("⏱️ Stop recording took: {:?}", start_time.elapsed());
     
-    let word_stats_time = word_stats_start.elapsed();
-    println!("⏱️ Word stats update took: {:?}", word_stats_time);
+    let dictation = app.state::<profiles::ProfileState>().current();
+    let text = finish_recording(&app, audio, dictation).await?;
     
-    let total_time = start_time.elapsed();
-    println!("⏱️ Total stop_recording_manual command took: {:?}", total_time);
+    println!("⏱️ Total stop_recording_manual command took: {:?}", start_time.elapsed());
     
     Ok(text)
 }
@@ -468,183 +714,70 @@ async fn stop_recording_manual(
 async fn stop_recording_chunked(
     app: tauri::AppHandle,
     audio_manager: State<'_, Arc<AudioManager>>,
-    whisper_model: State<'_, Arc<Mutex<WhisperModel>>>,
 ) -> Result<String, String> {
     let start_time = std::time::Instant::now();
     
-    let (audio_data, sample_rate, peak_level) = audio_manager.stop_recording().await?;
-    let stop_recording_time = start_time.elapsed();
-    println!("⏱️ Stop recording took: {:?}", stop_recording_time);
+    let audio = audio_manager.stop_recording().await?;
+    println!("⏱️ Stop recording took: {:?}", start_time.elapsed());
     
-    const SILENCE_THRESHOLD: f32 = 0.01;
+    let dictation = app.state::<profiles::ProfileState>().current();
+    let text = finish_recording(&app, audio, dictation).await?;
     
-    if audio_data.is_empty() || peak_level < SILENCE_THRESHOLD {
-        println!("🔇 Skipping transcription - no meaningful audio detected (peak level: {:.4})", peak_level);
-        return Ok(String::new());
-    }
+    println!("⏱️ Total stop_recording_chunked command took: {:?}", start_time.elapsed());
     
-    let audio_duration_secs = audio_data.len() as f32 / sample_rate as f32;
-    println!("🎙️ Audio duration: {:.2}s ({} samples at {} Hz)", audio_duration_secs, audio_data.len(), sample_rate);
+    sound::play_complete_sound(&app);
     
-    let transcribe_start = std::time::Instant::now();
-    let text = {
-        let store = app.store("settings.json").map_err(|e| e.to_string())?;
-        let key_opt = match store.get("groq_api_key") {
-            Some(val) => val.as_str().map(|s| s.to_string()),
-            None => None,
-        };
-        match key_opt {
-            Some(key) => {
-                println!("☁️ Using Groq for transcription");
-                let settings = AppSettings::get_or_default(&app);
-                groq::transcribe_with_groq(&audio_data, sample_rate, settings.whisper_language, &key).await?
-            }
-            None => {
-                println!("🧠 Using local Whisper for transcription");
-                let model = whisper_model.lock().unwrap();
-                let cfg = whisper::WhisperRuntimeConfig {
-                    language: AppSettings::get_or_default(&app).whisper_language,
-                    temperature: AppSettings::get_or_default(&app).whisper_temperature,
-                };
-                model.transcribe_with_config(&audio_data, sample_rate, &cfg)?
-            }
-        }
-    };
-    let transcribe_time = transcribe_start.elapsed();
-    println!("⏱️ Transcription took: {:?} (RTF: {:.2}x)", transcribe_time, transcribe_time.as_secs_f32() / audio_duration_secs);
-    
-    TranscriptionProgress {
-        text: text.clone(),
-        is_final: true,
-    }.emit(&app).ok();
-    
-    let trimmed_text = text.trim();
-    if trimmed_text.chars().all(|c| c.is_whitespace() || c.is_ascii_punctuation()) {
-        println!("🔇 Skipping transcription - only contains punctuation/whitespace: '{}'", trimmed_text);
-        return Ok(String::new());
-    }
-    
-    let words = text.split_whitespace().count() as u32;
-    if words > 0 || audio_data.len() > 0 {
-        let end_time = chrono::Utc::now().timestamp_millis();
-        let mut settings = AppSettings::get_or_default(&app);
-        
-        let session_duration_ms = if let Some(start) = settings.current_session_start {
-            (end_time - start) as f64
-        } else {
-            0.0
-        };
-        
-        settings.word_count += words;
-        settings.total_recording_time_ms += session_duration_ms;
-        settings.last_recording_time = Some(end_time);
-        
-        let overall_wpm = if settings.total_recording_time_ms > 0.0 {
-            (settings.word_count as f32 / (settings.total_recording_time_ms as f32 / 60000.0))
-        } else {
-            0.0
-        };
-        
-        let session_wpm = if session_duration_ms > 0.0 && words > 10 {
-            (words as f32 / (session_duration_ms as f32 / 60000.0))
-        } else {
-            0.0
-        };
-        
-        settings.current_session_start = None;
-        
-        AppSettings::set(&app, &settings)?;
-        
-        RecordingStatsUpdated {
-            total_words: settings.word_count,
-            total_time_ms: settings.total_recording_time_ms,
-            overall_wpm,
-            session_words: words,
-            session_time_ms: session_duration_ms,
-            session_wpm,
-        }.emit(&app).ok();
-        
-        WordCountUpdated { count: settings.word_count }.emit(&app).ok();
-        
-        if !text.is_empty() {
-            let store = app.store("settings.json").map_err(|e| e.to_string())?;
-            let model_used = if store.get("groq_api_key").is_some() {
-                Some("groq/whisper-large-v3".to_string())
-            } else {
-                settings.selected_model.clone()
-            };
-            let transcript = Transcript {
-                id: uuid::Uuid::new_v4().to_string(),
-                text: text.clone(),
-                timestamp: chrono::Utc::now().timestamp_millis() as f64,
-                duration_ms: session_duration_ms,
-                word_count: words,
-                wpm: session_wpm,
-                model_used,
-            };
-            
-            let mut store = TranscriptStore::load(&app).unwrap_or_default();
-            store.add_transcript(transcript);
-            let _ = store.save(&app);
-        }
-    }
-    
-    let total_time = start_time.elapsed();
-    println!("⏱️ Total stop_recording_chunked command took: {:?}", total_time);
-    
-    sound::play_complete_sound(&app);
-    
-    Ok(text)
-}
-
-#[tauri::command]
-#[specta::specta]
-fn request_microphone_permission(app: tauri::AppHandle) -> Result<bool, String> {
-    Permissions::request_permission("microphone")?;
-    let _ = tray::update_tray_menu(&app);
-    Ok(true)
-}
-
-#[tauri::command]
-#[specta::specta]
-fn request_accessibility_permission(app: tauri::AppHandle) -> Result<bool, String> {
-    Permissions::request_permission("accessibility")?;
-    let _ = tray::update_tray_menu(&app);
-    Ok(true)
-}
-
-#[tauri::command]
-#[specta::specta]
-fn refresh_permissions(app: tauri::AppHandle) -> Result<Permissions, String> {
-    let permissions = Permissions::check();
-    let _ = tray::update_tray_menu(&app);
-    Ok(permissions)
-}
-
-#[tauri::command]
-#[specta::specta]
-fn get_word_count(app: tauri::AppHandle) -> Result<u32, String> {
-    let settings = AppSettings::get_or_default(&app);
-    Ok(settings.word_count)
-}
-
-#[tauri::command]
-#[specta::specta]
-fn update_word_count(app: tauri::AppHandle, count: u32) -> Result<(), String> {
-    let mut settings = AppSettings::get_or_default(&app);
-    settings.word_count = count;
-    AppSettings::set(&app, &settings)
-}
-
-#[tauri::command]
-#[specta::specta]
-fn get_recording_stats(app: tauri::AppHandle) -> Result<RecordingStatsUpdated, String> {
-    let settings = AppSettings::get_or_default(&app);
-    
-    let overall_wpm = if settings.total_recording_time_ms > 0.0 {
-        (settings.word_count as f32 / (settings.total_recording_time_ms as f32 / 60000.0))
-    } else {
-        0.0
+    Ok(text)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn request_microphone_permission(app: tauri::AppHandle) -> Result<bool, String> {
+    Permissions::request_permission("microphone")?;
+    let _ = tray::update_tray_menu(&app);
+    Ok(true)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn request_accessibility_permission(app: tauri::AppHandle) -> Result<bool, String> {
+    Permissions::request_permission("accessibility")?;
+    let _ = tray::update_tray_menu(&app);
+    Ok(true)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn refresh_permissions(app: tauri::AppHandle) -> Result<Permissions, String> {
+    let permissions = Permissions::check();
+    let _ = tray::update_tray_menu(&app);
+    Ok(permissions)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_word_count(app: tauri::AppHandle) -> Result<u32, String> {
+    let settings = AppSettings::get_or_default(&app);
+    Ok(settings.word_count)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn update_word_count(app: tauri::AppHandle, count: u32) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.word_count = count;
+    AppSettings::set(&app, &settings)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_recording_stats(app: tauri::AppHandle) -> Result<RecordingStatsUpdated, String> {
+    let settings = AppSettings::get_or_default(&app);
+    
+    let overall_wpm = if settings.total_recording_time_ms > 0.0 {
+        (settings.word_count as f32 / (settings.total_recording_time_ms as f32 / 60000.0))
+    } else {
+        0.0
     };
     
     Ok(RecordingStatsUpdated {
@@ -664,14 +797,21 @@ fn get_hotkey(app: tauri::AppHandle) -> Result<Option<String>, String> {
     Ok(settings.hotkey)
 }
 
+/// The global shortcut that listens for a hotkey setting. Left and right
+/// modifiers share one, and "fn" has its own listener instead.
+fn shortcut_key(hotkey: &str) -> Option<&'static str> {
+    match hotkey {
+        "rightOption" | "leftOption" => Some("Alt"),
+        "leftControl" | "rightControl" => Some("Control"),
+        "rightCommand" => Some("Meta"),
+        "rightShift" => Some("Shift"),
+        _ => None,
+    }
+}
+
 #[tauri::command]
 #[specta::specta]
-fn set_hotkey(
-    app: tauri::AppHandle, 
-    hotkey: String, 
-    audio_manager: State<'_, Arc<AudioManager>>,
-    bubble_task_state: State<'_, BubbleShowTaskState>
-) -> Result<(), String> {
+fn set_hotkey(app: tauri::AppHandle, hotkey: String) -> Result<(), String> {
     println!("📌 Setting hotkey: {}", &hotkey);
     
     let mut settings = AppSettings::get_or_default(&app);
@@ -680,119 +820,263 @@ fn set_hotkey(
     
     if hotkey == "fn" {
         println!("✅ Fn key selected - will use FnKeyStateChanged events");
-        return Ok(());
+        return register_secondary_hotkeys(&app);
     }
     
-    let shortcut_key = match hotkey.as_str() {
-        "rightOption" => "Alt",
-        "leftOption" => "Alt", 
-        "leftControl" => "Control",
-        "rightControl" => "Control",
-        "rightCommand" => "Meta",
-        "rightShift" => "Shift",
-        _ => return Err(format!("Unsupported hotkey: {}", hotkey))
-    };
+    let shortcut_key = shortcut_key(&hotkey).ok_or_else(|| format!("Unsupported hotkey: {}", hotkey))?;
     
     let shortcut_manager = app.global_shortcut();
     
-    let settings = AppSettings::get_or_default(&app);
-    if let Some(old_hotkey) = settings.hotkey {
-        if old_hotkey != "fn" {
-            let old_shortcut_key = match old_hotkey.as_str() {
-                "rightOption" | "leftOption" => "Alt",
-                "leftControl" | "rightControl" => "Control",
-                "rightCommand" => "Meta",
-                "rightShift" => "Shift",
-                _ => ""
-            };
-            if !old_shortcut_key.is_empty() && shortcut_manager.is_registered(old_shortcut_key) {
-                println!("🔓 Unregistering old hotkey: {}", old_shortcut_key);
-                shortcut_manager.unregister(old_shortcut_key).map_err(|e| e.to_string())?;
-            }
-        }
+    if shortcut_manager.is_registered(shortcut_key) {
+        println!("🔓 Unregistering old hotkey: {}", shortcut_key);
+        shortcut_manager.unregister(shortcut_key).map_err(|e| e.to_string())?;
     }
     
     let app_handle = app.clone();
-    let audio_manager_clone = audio_manager.inner().clone();
     let hotkey_str = hotkey.clone();
-    let bubble_handle = bubble_task_state.handle.clone();
     
     shortcut_manager
         .on_shortcut(shortcut_key, move |_app, _shortcut, event| {
-            match event.state() {
-                ShortcutState::Pressed => {
-                    println!("🎤 Hotkey pressed: {} - Starting recording", &hotkey_str);
-                    HotkeyPressed { pressed: true }.emit(&app_handle).ok();
-                    RecordingStateChanged { is_recording: true }.emit(&app_handle).ok();
-                    let audio_manager = audio_manager_clone.clone();
-                    let app_handle_for_bubble = app_handle.clone();
-                    let app_handle_for_recording = app_handle.clone();
-                    let bubble_handle_clone = bubble_handle.clone();
-                    let handle = tauri::async_runtime::spawn(async move {
-                        let start_time = chrono::Utc::now().timestamp_millis();
-                        let mut settings = AppSettings::get_or_default(&app_handle_for_recording);
-                        settings.current_session_start = Some(start_time);
-                        if settings.first_recording_time.is_none() {
-                            settings.first_recording_time = Some(start_time);
-                        }
-                        let _ = AppSettings::set(&app_handle_for_recording, &settings);
-                        
-                        let _ = audio_manager.start_recording().await;
-                        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
-                        let _ = window::show_bubble_window(&app_handle_for_bubble);
-                    });
-                    *bubble_handle_clone.lock().unwrap() = Some(handle);
+            handle_dictation_hotkey(&app_handle, &hotkey_str, event.state(), None);
+        })
+        .map_err(|e| format!("Failed to register hotkey: {}", e))?;
+    
+    println!("✅ Hotkey registered successfully: {}", &hotkey);
+    
+    // Drops shortcuts left over from the previous hotkey.
+    register_secondary_hotkeys(&app)
+}
+
+/// Registers the command hotkey and the hotkeys bound to rewrite prompts,
+/// which dictate like the main hotkey with their prompt applied. Every other
+/// modifier shortcut is released, so this also cleans up after the main
+/// hotkey changes.
+fn register_secondary_hotkeys(app: &tauri::AppHandle) -> Result<(), String> {
+    let settings = AppSettings::get_or_default(app);
+    let main_key = settings.hotkey.as_deref().and_then(shortcut_key);
+    let shortcut_manager = app.global_shortcut();
+    
+    for key in ["Alt", "Control", "Meta", "Shift"] {
+        if Some(key) != main_key && shortcut_manager.is_registered(key) {
+            shortcut_manager.unregister(key).map_err(|e| e.to_string())?;
+        }
+    }
+    
+    let command_key = settings
+        .command_hotkey
+        .as_deref()
+        .and_then(shortcut_key)
+        .filter(|_| command_mode::SUPPORTED);
+    if let (Some(hotkey), Some(key)) = (settings.command_hotkey.clone(), command_key.filter(|k| Some(*k) != main_key)) {
+        let app_handle = app.clone();
+        shortcut_manager
+            .on_shortcut(key, move |_app, _shortcut, event| {
+                handle_command_hotkey(&app_handle, &hotkey, event.state());
+            })
+            .map_err(|e| format!("Failed to register command hotkey: {}", e))?;
+        println!("✅ Registered {} for voice commands", key);
+    }
+    
+    for prompt in &settings.rewrite.prompts {
+        let Some(hotkey) = prompt.hotkey.clone() else {
+            continue;
+        };
+        let Some(key) = shortcut_key(&hotkey).filter(|k| Some(*k) != main_key && Some(*k) != command_key) else {
+            eprintln!("Skipping hotkey {} for prompt \"{}\"", hotkey, prompt.name);
+            continue;
+        };
+        let app_handle = app.clone();
+        let prompt_id = prompt.id.clone();
+        shortcut_manager
+            .on_shortcut(key, move |_app, _shortcut, event| {
+                handle_dictation_hotkey(&app_handle, &hotkey, event.state(), Some(prompt_id.clone()));
+            })
+            .map_err(|e| format!("Failed to register hotkey for \"{}\": {}", prompt.name, e))?;
+        println!("✅ Registered {} for rewrite prompt \"{}\"", key, prompt.name);
+    }
+    Ok(())
+}
+
+/// Starts recording when a dictation hotkey goes down and transcribes and
+/// inserts when it comes back up. `rewrite_prompt_id` is the prompt bound to
+/// the hotkey, if any.
+fn handle_dictation_hotkey(
+    app_handle: &tauri::AppHandle,
+    hotkey: &str,
+    state: ShortcutState,
+    rewrite_prompt_id: Option<String>,
+) {
+    let bubble_handle = app_handle.state::<BubbleShowTaskState>().handle.clone();
+    match state {
+        ShortcutState::Pressed => {
+            println!("🎤 Hotkey pressed: {} - Starting recording", hotkey);
+            begin_dictation(app_handle, rewrite_prompt_id);
+            HotkeyPressed { pressed: true }.emit(app_handle).ok();
+            RecordingStateChanged { is_recording: true }.emit(app_handle).ok();
+            let audio_manager = app_handle.state::<Arc<AudioManager>>().inner().clone();
+            let app_handle_for_bubble = app_handle.clone();
+            let app_handle_for_recording = app_handle.clone();
+            let bubble_handle_clone = bubble_handle.clone();
+            let handle = tauri::async_runtime::spawn(async move {
+                let start_time = chrono::Utc::now().timestamp_millis();
+                let mut settings = AppSettings::get_or_default(&app_handle_for_recording);
+                settings.current_session_start = Some(start_time);
+                if settings.first_recording_time.is_none() {
+                    settings.first_recording_time = Some(start_time);
                 }
-                ShortcutState::Released => {
-                    println!("🛑 Hotkey released: {} - Stopping recording", &hotkey_str);
-                    HotkeyPressed { pressed: false }.emit(&app_handle).ok();
-                    RecordingStateChanged { is_recording: false }.emit(&app_handle).ok();
-                    
-                    if let Some(handle) = bubble_handle.lock().unwrap().take() {
-                        handle.abort();
-                        println!("🚫 Cancelled bubble show task");
-                    }
-                    
-                    let app_handle_hide = app_handle.clone();
-                    tauri::async_runtime::spawn(async move {
-                        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
-                        let _ = window::hide_bubble_window(&app_handle_hide);
-                    });
-                    let app_handle_clone = app_handle.clone();
-                    tauri::async_runtime::spawn(async move {
-                        match app_handle_clone.try_state::<Arc<AudioManager>>() {
-                            Some(audio_state) => {
-                                match app_handle_clone.try_state::<Arc<Mutex<WhisperModel>>>() {
-                                    Some(whisper_state) => {
-                                        match stop_recording_chunked(app_handle_clone.clone(), audio_state, whisper_state).await {
-                                            Ok(text) => {
-                                                if !text.is_empty() {
-                                                    let _ = insert_text_at_cursor(text);
-                                                }
-                                            }
-                                            Err(e) => {
-                                                eprintln!("Failed to transcribe: {}", e);
-                                            }
-                                        }
-                                    }
-                                    None => {
-                                        eprintln!("Failed to get whisper model state");
-                                    }
+                let _ = AppSettings::set(&app_handle_for_recording, &settings);
+                
+                let _ = audio_manager.start_recording().await;
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+                let _ = window::show_bubble_window(&app_handle_for_bubble);
+            });
+            *bubble_handle_clone.lock().unwrap() = Some(handle);
+        }
+        ShortcutState::Released => {
+            println!("🛑 Hotkey released: {} - Stopping recording", hotkey);
+            HotkeyPressed { pressed: false }.emit(app_handle).ok();
+            RecordingStateChanged { is_recording: false }.emit(app_handle).ok();
+            
+            if let Some(handle) = bubble_handle.lock().unwrap().take() {
+                handle.abort();
+                println!("🚫 Cancelled bubble show task");
+            }
+            
+            let app_handle_hide = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+                let _ = window::hide_bubble_window(&app_handle_hide);
+            });
+            let app_handle_clone = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                match app_handle_clone.try_state::<Arc<AudioManager>>() {
+                    Some(audio_state) => {
+                        match stop_recording_chunked(app_handle_clone.clone(), audio_state).await {
+                            Ok(text) => {
+                                if !text.is_empty() {
+                                    let _ = insert_text_at_cursor(app_handle_clone.clone(), text);
                                 }
                             }
-                            None => {
-                                eprintln!("Failed to get audio manager state");
+                            Err(e) => {
+                                eprintln!("Failed to transcribe: {}", e);
                             }
                         }
-                    });
+                    }
+                    None => {
+                        eprintln!("Failed to get audio manager state");
+                    }
                 }
+            });
+        }
+    }
+}
+
+/// Voice command mode: while the command hotkey is held, the selection is
+/// copied and an instruction recorded. On release both go to the rewrite
+/// endpoint and the reply replaces the selection.
+fn handle_command_hotkey(app_handle: &tauri::AppHandle, hotkey: &str, state: ShortcutState) {
+    let bubble_handle = app_handle.state::<BubbleShowTaskState>().handle.clone();
+    match state {
+        ShortcutState::Pressed => {
+            println!("🗣️ Command hotkey pressed: {} - Recording instruction", hotkey);
+            begin_dictation(app_handle, None);
+            HotkeyPressed { pressed: true }.emit(app_handle).ok();
+            RecordingStateChanged { is_recording: true }.emit(app_handle).ok();
+            
+            let capture = tauri::async_runtime::spawn_blocking(command_mode::capture_selection);
+            *app_handle.state::<command_mode::CommandState>().selection.lock().unwrap() = Some(capture);
+            
+            let audio_manager = app_handle.state::<Arc<AudioManager>>().inner().clone();
+            let app_handle_for_bubble = app_handle.clone();
+            let handle = tauri::async_runtime::spawn(async move {
+                let _ = audio_manager.start_recording().await;
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+                let _ = window::show_bubble_window(&app_handle_for_bubble);
+            });
+            *bubble_handle.lock().unwrap() = Some(handle);
+        }
+        ShortcutState::Released => {
+            println!("🛑 Command hotkey released: {} - Running instruction", hotkey);
+            HotkeyPressed { pressed: false }.emit(app_handle).ok();
+            RecordingStateChanged { is_recording: false }.emit(app_handle).ok();
+            
+            if let Some(handle) = bubble_handle.lock().unwrap().take() {
+                handle.abort();
             }
-        })
-        .map_err(|e| format!("Failed to register hotkey: {}", e))?;
+            let _ = window::hide_bubble_window(app_handle);
+            
+            let app_handle_clone = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                match run_voice_command(&app_handle_clone).await {
+                    Ok(Some(text)) => {
+                        // Only copying would leave the selection as it was, so that
+                        // setting falls back to pasting here.
+                        let method = match app_handle_clone.state::<profiles::ProfileState>().current().profile {
+                            Some(profile) if profile.insertion == profiles::InsertionMethod::Type => {
+                                profiles::InsertionMethod::Type
+                            }
+                            _ => profiles::InsertionMethod::Paste,
+                        };
+                        if let Err(e) = insert_text(&app_handle_clone, &text, method) {
+                            eprintln!("Failed to replace selection: {}", e);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        eprintln!("Voice command failed: {}", e);
+                        let _ = app_handle_clone
+                            .notification()
+                            .builder()
+                            .title("Voice command failed")
+                            .body(format!("The selection was left unchanged.\n{}", e))
+                            .show();
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Transcribes the instruction just recorded and applies it to the captured
+/// selection. `None` when there was nothing to do.
+async fn run_voice_command(app: &tauri::AppHandle) -> Result<Option<String>, String> {
+    let capture = app.state::<command_mode::CommandState>().selection.lock().unwrap().take();
+    let audio_manager = app.state::<Arc<AudioManager>>().inner().clone();
+    let (audio_data, sample_rate, peak_level) = audio_manager.stop_recording().await?;
     
-    println!("✅ Hotkey registered successfully: {}", &hotkey);
+    let selection = match capture {
+        Some(capture) => capture.await.map_err(|e| e.to_string())??,
+        None => None,
+    };
+    let Some(selection) = selection else {
+        println!("🔇 Skipping voice command - nothing is selected");
+        return Ok(None);
+    };
+    if !pipeline::has_meaningful_audio(&audio_data, peak_level) {
+        println!("🔇 Skipping voice command - no meaningful audio detected (peak level: {:.4})", peak_level);
+        return Ok(None);
+    }
     
-    Ok(())
+    let dictation = app.state::<profiles::ProfileState>().current();
+    let profile = dictation.profile.as_ref();
+    let cfg = whisper_runtime_config(app, profile);
+    let backend = app
+        .state::<backend::BackendRegistry>()
+        .resolve(app, profile.and_then(|p| p.backend_id.as_deref()));
+    let backend::BackendOutput { output, .. } =
+        backend::transcribe_with_fallback(app, backend, &audio_data, sample_rate, &cfg).await?;
+    let output = pipeline::drop_low_confidence_segments(output);
+    let Some(instruction) = pipeline::finalize_text(&output.text) else {
+        println!("🔇 Skipping voice command - no instruction heard");
+        return Ok(None);
+    };
+    
+    println!("🗣️ Voice command: {}", instruction);
+    let endpoint = AppSettings::get_or_default(app).rewrite.endpoint;
+    let message = command_mode::user_message(&instruction, &selection);
+    stream_completion(app, &endpoint, command_mode::SYSTEM_PROMPT, &message)
+        .await
+        .map(Some)
 }
 
 #[tauri::command]
@@ -808,7 +1092,17 @@ fn validate_hotkey(_app: tauri::AppHandle, hotkey: String) -> Result<bool, Strin
 
 #[tauri::command]
 #[specta::specta]
-fn insert_text_at_cursor(text: String) -> Result<(), String> {
+fn insert_text_at_cursor(app: tauri::AppHandle, text: String) -> Result<(), String> {
+    let method = app
+        .state::<profiles::ProfileState>()
+        .current()
+        .profile
+        .map(|p| p.insertion)
+        .unwrap_or_default();
+    insert_text(&app, &text, method)
+}
+
+fn insert_text(app: &tauri::AppHandle, text: &str, method: profiles::InsertionMethod) -> Result<(), String> {
     #[cfg(target_os = "macos")]
     {
         use cocoa::base::{nil, id};
@@ -817,6 +1111,12 @@ fn insert_text_at_cursor(text: String) -> Result<(), String> {
         use core_graphics::event::{CGEvent, CGEventTapLocation, CGKeyCode};
         use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
         
+        if method == profiles::InsertionMethod::Type {
+            editing::keys::type_text(text)?;
+            app.state::<editing::InsertionHistory>().record(text);
+            return Ok(());
+        }
+        
         unsafe {
             let pool = NSAutoreleasePool::new(nil);
             
@@ -827,11 +1127,17 @@ fn insert_text_at_cursor(text: String) -> Result<(), String> {
             let old_items: id = msg_send![pasteboard, readObjectsForClasses:nil options:nil];
             
             let _: () = msg_send![pasteboard, clearContents];
-            let ns_string = NSString::alloc(nil).init_str(&text);
+            let ns_string = NSString::alloc(nil).init_str(text);
             let array_class = class!(NSArray);
             let string_array: id = msg_send![array_class, arrayWithObject: ns_string];
             let _: () = msg_send![pasteboard, writeObjects: string_array];
             
+            if method == profiles::InsertionMethod::Clipboard {
+                println!("📋 Copied transcription to the clipboard");
+                let _: () = msg_send![pool, release];
+                return Ok(());
+            }
+            
             let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState).unwrap();
             
             if let Ok(cmd_down) = CGEvent::new_keyboard_event(source.clone(), 0x37 as CGKeyCode, true) {
@@ -862,184 +1168,865 @@ fn insert_text_at_cursor(text: String) -> Result<(), String> {
             
             let _: () = msg_send![pool, release];
         }
+        
+        app.state::<editing::InsertionHistory>().record(text);
+    }
+    
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, text, method);
+        return Err("Text insertion not implemented for this platform".to_string());
+    }
+    
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn show_main_window(app: tauri::AppHandle) -> Result<(), String> {
+    window::show_main_window(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_transcripts(app: tauri::AppHandle, limit: Option<u32>) -> Result<Vec<Transcript>, String> {
+    let store = TranscriptStore::load(&app).unwrap_or_default();
+    Ok(store.get_transcripts(limit))
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_transcript_stats(app: tauri::AppHandle) -> Result<transcripts::TranscriptStats, String> {
+    let store = TranscriptStore::load(&app).unwrap_or_default();
+    Ok(store.calculate_stats())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn delete_transcript(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let mut store = TranscriptStore::load(&app).unwrap_or_default();
+    store.delete_transcript(&id)?;
+    store.save(&app)?;
+    Ok(())
+}
+
+/// Saves a user edit and learns from the words that changed.
+#[tauri::command]
+#[specta::specta]
+fn update_transcript_text(app: tauri::AppHandle, id: String, text: String) -> Result<Transcript, String> {
+    let mut store = TranscriptStore::load(&app)?;
+    let previous = store.update_text(&id, text.clone())?;
+    store.save(&app)?;
+    
+    let settings = AppSettings::get_or_default(&app);
+    if settings.corrections.enabled {
+        // Diff against the previous version, not the original, so re-saving an
+        // edit doesn't count the same fix twice.
+        let substitutions = corrections::word_substitutions(&previous, &text);
+        if !substitutions.is_empty() {
+            let mut corrections = corrections::CorrectionStore::load(&app).unwrap_or_default();
+            corrections.record(&substitutions);
+            corrections.save(&app)?;
+        }
+    }
+    
+    store
+        .get_transcript_by_id(&id)
+        .cloned()
+        .ok_or_else(|| "Transcript not found".to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn export_transcripts(
+    app: tauri::AppHandle,
+    ids: Option<Vec<String>>,
+    format: transcripts::ExportFormat,
+) -> Result<String, String> {
+    let store = TranscriptStore::load(&app).unwrap_or_default();
+    Ok(store.export(ids.as_deref(), format))
+}
+
+#[tauri::command]
+#[specta::specta]
+fn clear_all_transcripts(app: tauri::AppHandle) -> Result<(), String> {
+    let mut store = TranscriptStore::load(&app).unwrap_or_default();
+    store.clear_all();
+    store.save(&app)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_snippets(app: tauri::AppHandle) -> Result<Vec<snippets::Snippet>, String> {
+    let store = snippets::SnippetStore::load(&app).unwrap_or_default();
+    Ok(store.snippets)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn create_snippet(app: tauri::AppHandle, trigger: String, expansion: String) -> Result<snippets::Snippet, String> {
+    let mut store = snippets::SnippetStore::load(&app)?;
+    let snippet = store.add_snippet(trigger, expansion)?;
+    store.save(&app)?;
+    Ok(snippet)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn update_snippet(
+    app: tauri::AppHandle,
+    id: String,
+    trigger: String,
+    expansion: String,
+) -> Result<snippets::Snippet, String> {
+    let mut store = snippets::SnippetStore::load(&app)?;
+    let snippet = store.update_snippet(&id, trigger, expansion)?;
+    store.save(&app)?;
+    Ok(snippet)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn delete_snippet(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let mut store = snippets::SnippetStore::load(&app)?;
+    store.delete_snippet(&id)?;
+    store.save(&app)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_replacement_rules(app: tauri::AppHandle) -> Result<Vec<dictionary::ReplacementRule>, String> {
+    let store = dictionary::DictionaryStore::load(&app).unwrap_or_default();
+    Ok(store.rules)
+}
+
+/// Creates a rule when `rule.id` is empty, otherwise updates the existing one.
+#[tauri::command]
+#[specta::specta]
+fn save_replacement_rule(
+    app: tauri::AppHandle,
+    rule: dictionary::ReplacementRule,
+) -> Result<dictionary::ReplacementRule, String> {
+    let mut store = dictionary::DictionaryStore::load(&app)?;
+    let rule = store.upsert_rule(rule)?;
+    store.save(&app)?;
+    Ok(rule)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn delete_replacement_rule(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let mut store = dictionary::DictionaryStore::load(&app)?;
+    store.delete_rule(&id)?;
+    store.save(&app)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn reset_replacement_hit_counts(app: tauri::AppHandle) -> Result<(), String> {
+    let mut store = dictionary::DictionaryStore::load(&app)?;
+    store.reset_hit_counts();
+    store.save(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn export_replacement_rules_csv(app: tauri::AppHandle) -> Result<String, String> {
+    dictionary::DictionaryStore::load(&app)?.export_csv()
+}
+
+/// Returns how many rules were added.
+#[tauri::command]
+#[specta::specta]
+fn import_replacement_rules_csv(app: tauri::AppHandle, csv: String) -> Result<u32, String> {
+    let mut store = dictionary::DictionaryStore::load(&app)?;
+    let imported = store.import_csv(&csv)?;
+    store.save(&app)?;
+    Ok(imported)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_vocabulary(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    Ok(AppSettings::get_or_default(&app).vocabulary)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_vocabulary(app: tauri::AppHandle, vocabulary: Vec<String>) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.vocabulary = vocabulary
+        .into_iter()
+        .map(|term| term.trim().to_string())
+        .filter(|term| !term.is_empty())
+        .collect();
+    AppSettings::set(&app, &settings)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_correction_settings(app: tauri::AppHandle) -> Result<corrections::CorrectionSettings, String> {
+    Ok(AppSettings::get_or_default(&app).corrections)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_correction_settings(
+    app: tauri::AppHandle,
+    corrections: corrections::CorrectionSettings,
+) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.corrections = corrections;
+    AppSettings::set(&app, &settings)
+}
+
+/// Substitutions the user keeps making by hand, skipping ones a replacement
+/// rule or vocabulary term already covers.
+#[tauri::command]
+#[specta::specta]
+fn get_correction_suggestions(app: tauri::AppHandle) -> Result<Vec<corrections::CorrectionSuggestion>, String> {
+    let settings = AppSettings::get_or_default(&app);
+    let dictionary = dictionary::DictionaryStore::load(&app).unwrap_or_default();
+    let suggestions = corrections::CorrectionStore::load(&app)
+        .unwrap_or_default()
+        .suggestions(settings.corrections.min_occurrences)
+        .into_iter()
+        .filter(|s| {
+            !dictionary.rules.iter().any(|r| r.find.eq_ignore_ascii_case(&s.from))
+                && !settings.vocabulary.contains(&s.to)
+        })
+        .collect();
+    Ok(suggestions)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn accept_correction_suggestion(
+    app: tauri::AppHandle,
+    from: String,
+    to: String,
+    kind: corrections::SuggestionKind,
+) -> Result<(), String> {
+    match kind {
+        corrections::SuggestionKind::ReplacementRule => {
+            let mut dictionary = dictionary::DictionaryStore::load(&app)?;
+            dictionary.upsert_rule(dictionary::ReplacementRule {
+                id: String::new(),
+                find: from.clone(),
+                replace: to.clone(),
+                mode: dictionary::MatchMode::WholeWord,
+                case_sensitive: false,
+                preserve_case: false,
+                enabled: true,
+                hit_count: 0,
+                last_hit_at: None,
+            })?;
+            dictionary.save(&app)?;
+        }
+        corrections::SuggestionKind::Vocabulary => {
+            let mut settings = AppSettings::get_or_default(&app);
+            if !settings.vocabulary.contains(&to) {
+                settings.vocabulary.push(to.clone());
+                AppSettings::set(&app, &settings)?;
+            }
+        }
+    }
+    
+    let mut corrections = corrections::CorrectionStore::load(&app)?;
+    corrections.resolve(&from, &to);
+    corrections.save(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn dismiss_correction_suggestion(app: tauri::AppHandle, from: String, to: String) -> Result<(), String> {
+    let mut corrections = corrections::CorrectionStore::load(&app)?;
+    corrections.dismiss(&from, &to)?;
+    corrections.save(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn is_fn_key_pressed(_app: tauri::AppHandle) -> Result<bool, String> {
+    let is_pressed = fn_key_monitor::is_fn_pressed();
+    println!("🔍 is_fn_key_pressed command called - result: {}", is_pressed);
+    Ok(is_pressed)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn test_fn_key(app: tauri::AppHandle) -> Result<String, String> {
+    let new_state = fn_key_monitor::toggle_fn_pressed();
+    println!("🔑 Fn key toggled to: {}", new_state);
+    
+    FnKeyStateChanged { is_pressed: new_state }.emit(&app).ok();
+    
+    Ok(format!("Fn key state toggled to: {}", new_state))
+}
+
+#[tauri::command]
+#[specta::specta]
+fn check_model_downloaded(app: tauri::AppHandle) -> Result<bool, String> {
+    let settings = AppSettings::get_or_default(&app);
+    if let Some(model_id) = settings.selected_model {
+        Ok(WhisperModel::is_downloaded(&model_id))
+    } else {
+        Ok(false)
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn download_whisper_model(
+    app: tauri::AppHandle,
+    whisper_model: State<'_, Arc<Mutex<WhisperModel>>>,
+) -> Result<(), String> {
+    let settings = AppSettings::get_or_default(&app);
+    let model_id = settings.selected_model.ok_or_else(|| "No model selected".to_string())?;
+    
+    WhisperModel::download(&app, &model_id).await?;
+    
+    let mut model = whisper_model.lock().unwrap();
+    model.load_model(Some(model_id))?;
+    
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_available_models(app: tauri::AppHandle) -> Result<Vec<whisper::WhisperModelInfo>, String> {
+    let recommended = benchmark::BenchmarkReport::load(&app)
+        .unwrap_or(None)
+        .and_then(|report| report.recommended_model);
+    
+    let mut models = whisper::WhisperModelInfo::all();
+    for model in &mut models {
+        model.is_recommended = recommended.as_deref() == Some(model.id.as_str());
+    }
+    Ok(models)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn benchmark_models(app: tauri::AppHandle) -> Result<benchmark::BenchmarkReport, String> {
+    tauri::async_runtime::spawn_blocking(move || benchmark::run_benchmark(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_benchmark_results(app: tauri::AppHandle) -> Result<Option<benchmark::BenchmarkReport>, String> {
+    benchmark::BenchmarkReport::load(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_downloaded_models() -> Result<Vec<String>, String> {
+    Ok(WhisperModel::get_downloaded_models())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn import_model_bundle(path: String) -> Result<model_bundle::BundleImportResult, String> {
+    tauri::async_runtime::spawn_blocking(move || model_bundle::import_bundle(std::path::Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn export_model_bundle(
+    destination: String,
+    model_ids: Option<Vec<String>>,
+) -> Result<model_bundle::BundleManifest, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        model_bundle::export_bundle(std::path::Path::new(&destination), model_ids.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_selected_model(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let settings = AppSettings::get_or_default(&app);
+    Ok(settings.selected_model)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_selected_model(
+    app: tauri::AppHandle,
+    whisper_model: State<'_, Arc<Mutex<WhisperModel>>>,
+    model_id: String,
+) -> Result<(), String> {
+    if whisper::WhisperModelInfo::get_by_id(&model_id).is_none() {
+        return Err(format!("Invalid model ID: {}", model_id));
+    }
+    
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.selected_model = Some(model_id.clone());
+    AppSettings::set(&app, &settings)?;
+    
+    if WhisperModel::is_downloaded(&model_id) {
+        let mut model = whisper_model.lock().unwrap();
+        model.load_model(Some(model_id))?;
+    }
+    
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_two_pass_settings(app: tauri::AppHandle) -> Result<(bool, Option<String>), String> {
+    let settings = AppSettings::get_or_default(&app);
+    Ok((settings.two_pass_enabled, settings.draft_model))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_two_pass_settings(
+    app: tauri::AppHandle,
+    whisper_model: State<'_, Arc<Mutex<WhisperModel>>>,
+    enabled: bool,
+    draft_model_id: Option<String>,
+) -> Result<(), String> {
+    if let Some(id) = &draft_model_id {
+        if whisper::WhisperModelInfo::get_by_id(id).is_none() {
+            return Err(format!("Invalid model ID: {}", id));
+        }
+    }
+    
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.two_pass_enabled = enabled;
+    settings.draft_model = draft_model_id.clone();
+    AppSettings::set(&app, &settings)?;
+    
+    let draft_to_load = draft_model_id.filter(|id| enabled && WhisperModel::is_downloaded(id));
+    let mut model = whisper_model.lock().unwrap();
+    model.load_draft_model(draft_to_load)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_model_path(app: tauri::AppHandle) -> Result<String, String> {
+    let settings = AppSettings::get_or_default(&app);
+    if let Some(model_id) = settings.selected_model {
+        if let Some(model_info) = whisper::WhisperModelInfo::get_by_id(&model_id) {
+            WhisperModel::get_model_path(&model_info.filename)
+                .map(|p| p.to_string_lossy().to_string())
+        } else {
+            Err("Invalid model ID".to_string())
+        }
+    } else {
+        Err("No model selected".to_string())
     }
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_groq_api_key(app: tauri::AppHandle, key: String) -> Result<(), String> {
+    openai_compat::set_api_key(&app, openai_compat::GROQ_PROVIDER_ID, key)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn clear_groq_api_key(app: tauri::AppHandle) -> Result<(), String> {
+    clear_provider_api_key(app, openai_compat::GROQ_PROVIDER_ID.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn has_groq_api_key(app: tauri::AppHandle) -> Result<bool, String> {
+    has_provider_api_key(app, openai_compat::GROQ_PROVIDER_ID.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_cloud_providers(app: tauri::AppHandle) -> Result<Vec<openai_compat::ProviderConfig>, String> {
+    Ok(openai_compat::providers(&app))
+}
+
+#[tauri::command]
+#[specta::specta]
+fn save_cloud_provider(app: tauri::AppHandle, provider: openai_compat::ProviderConfig) -> Result<(), String> {
+    provider.validate()?;
     
-    #[cfg(not(target_os = "macos"))]
-    {
-        return Err("Text insertion not implemented for this platform".to_string());
+    let mut settings = AppSettings::get_or_default(&app);
+    match settings.cloud_providers.iter_mut().find(|p| p.id == provider.id) {
+        Some(existing) => *existing = provider,
+        None => settings.cloud_providers.push(provider),
+    }
+    AppSettings::set(&app, &settings)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn delete_cloud_provider(app: tauri::AppHandle, provider_id: String) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.cloud_providers.retain(|p| p.id != provider_id);
+    if settings.active_backend.as_deref() == Some(provider_id.as_str()) {
+        settings.active_backend = Some(backend::LOCAL_BACKEND_ID.to_string());
     }
+    AppSettings::set(&app, &settings)?;
+    openai_compat::clear_api_key(&app, &provider_id)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_provider_api_key(app: tauri::AppHandle, provider_id: String, key: String) -> Result<(), String> {
+    openai_compat::set_api_key(&app, &provider_id, key)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn clear_provider_api_key(app: tauri::AppHandle, provider_id: String) -> Result<(), String> {
+    openai_compat::clear_api_key(&app, &provider_id)?;
     
+    let mut settings = AppSettings::get_or_default(&app);
+    let provider = openai_compat::providers(&app).into_iter().find(|p| p.id == provider_id);
+    let needs_key = provider.map(|p| p.requires_api_key()).unwrap_or(true);
+    if needs_key && settings.active_backend.as_deref() == Some(provider_id.as_str()) {
+        settings.active_backend = Some(backend::LOCAL_BACKEND_ID.to_string());
+        AppSettings::set(&app, &settings)?;
+    }
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-fn show_main_window(app: tauri::AppHandle) -> Result<(), String> {
-    window::show_main_window(&app)
+fn has_provider_api_key(app: tauri::AppHandle, provider_id: String) -> Result<bool, String> {
+    Ok(openai_compat::has_api_key(&app, &provider_id))
 }
 
 #[tauri::command]
 #[specta::specta]
-fn get_transcripts(app: tauri::AppHandle, limit: Option<u32>) -> Result<Vec<Transcript>, String> {
-    let store = TranscriptStore::load(&app).unwrap_or_default();
-    Ok(store.get_transcripts(limit))
+fn get_usage_summary(app: tauri::AppHandle) -> Result<usage::UsageSummary, String> {
+    Ok(usage::UsageLog::load(&app)?.summary())
 }
 
 #[tauri::command]
 #[specta::specta]
-fn get_transcript_stats(app: tauri::AppHandle) -> Result<transcripts::TranscriptStats, String> {
-    let store = TranscriptStore::load(&app).unwrap_or_default();
-    Ok(store.calculate_stats())
+fn get_usage_limits(app: tauri::AppHandle) -> Result<usage::UsageLimits, String> {
+    Ok(AppSettings::get_or_default(&app).usage_limits)
 }
 
 #[tauri::command]
 #[specta::specta]
-fn delete_transcript(app: tauri::AppHandle, id: String) -> Result<(), String> {
-    let mut store = TranscriptStore::load(&app).unwrap_or_default();
-    store.delete_transcript(&id)?;
-    store.save(&app)?;
-    Ok(())
+fn set_usage_limits(app: tauri::AppHandle, limits: usage::UsageLimits) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.usage_limits = limits;
+    AppSettings::set(&app, &settings)
 }
 
 #[tauri::command]
 #[specta::specta]
-fn clear_all_transcripts(app: tauri::AppHandle) -> Result<(), String> {
-    let mut store = TranscriptStore::load(&app).unwrap_or_default();
-    store.clear_all();
-    store.save(&app)?;
+fn get_privacy_mode(app: tauri::AppHandle) -> Result<bool, String> {
+    Ok(AppSettings::get_or_default(&app).privacy_mode)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_privacy_mode(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.privacy_mode = enabled;
+    AppSettings::set(&app, &settings)?;
+    println!("🔒 Privacy mode {}", if enabled { "enabled" } else { "disabled" });
+    
+    let _ = tray::update_tray_menu(&app);
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-fn is_fn_key_pressed(_app: tauri::AppHandle) -> Result<bool, String> {
-    let is_pressed = fn_key_monitor::is_fn_pressed();
-    println!("🔍 is_fn_key_pressed command called - result: {}", is_pressed);
-    Ok(is_pressed)
+fn get_egress_audit_log(app: tauri::AppHandle, limit: Option<u32>) -> Result<Vec<net::EgressAttempt>, String> {
+    net::read_audit_log(&app, limit)
 }
 
 #[tauri::command]
 #[specta::specta]
-fn test_fn_key(app: tauri::AppHandle) -> Result<String, String> {
-    let new_state = fn_key_monitor::toggle_fn_pressed();
-    println!("🔑 Fn key toggled to: {}", new_state);
-    
-    FnKeyStateChanged { is_pressed: new_state }.emit(&app).ok();
+fn get_network_settings(app: tauri::AppHandle) -> Result<net::NetworkSettings, String> {
+    Ok(AppSettings::get_or_default(&app).network)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_network_settings(app: tauri::AppHandle, network: net::NetworkSettings) -> Result<(), String> {
+    // Surface a bad proxy URL or CA bundle now rather than on the next download.
+    net::client_builder(&network)?
+        .build()
+        .map_err(|e| format!("Invalid network settings: {}", e))?;
     
-    Ok(format!("Fn key state toggled to: {}", new_state))
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.network = network;
+    AppSettings::set(&app, &settings)
 }
 
 #[tauri::command]
 #[specta::specta]
-fn check_model_downloaded(app: tauri::AppHandle) -> Result<bool, String> {
-    let settings = AppSettings::get_or_default(&app);
-    if let Some(model_id) = settings.selected_model {
-        Ok(WhisperModel::is_downloaded(&model_id))
-    } else {
-        Ok(false)
-    }
+fn get_post_processing_settings(app: tauri::AppHandle) -> Result<postprocess::PostProcessSettings, String> {
+    Ok(AppSettings::get_or_default(&app).post_processing)
 }
 
 #[tauri::command]
 #[specta::specta]
-async fn download_whisper_model(
+fn set_post_processing_settings(
     app: tauri::AppHandle,
-    whisper_model: State<'_, Arc<Mutex<WhisperModel>>>,
+    post_processing: postprocess::PostProcessSettings,
 ) -> Result<(), String> {
-    let settings = AppSettings::get_or_default(&app);
-    let model_id = settings.selected_model.ok_or_else(|| "No model selected".to_string())?;
-    
-    WhisperModel::download(&app, &model_id).await?;
-    
-    let mut model = whisper_model.lock().unwrap();
-    model.load_model(Some(model_id))?;
-    
-    Ok(())
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.post_processing = post_processing;
+    AppSettings::set(&app, &settings)
 }
 
 #[tauri::command]
 #[specta::specta]
-fn get_available_models() -> Result<Vec<whisper::WhisperModelInfo>, String> {
-    Ok(whisper::WhisperModelInfo::all())
+fn get_formatting_settings(app: tauri::AppHandle) -> Result<formatting::FormattingSettings, String> {
+    Ok(AppSettings::get_or_default(&app).formatting)
 }
 
 #[tauri::command]
 #[specta::specta]
-fn get_downloaded_models() -> Result<Vec<String>, String> {
-    Ok(WhisperModel::get_downloaded_models())
+fn set_formatting_settings(
+    app: tauri::AppHandle,
+    formatting: formatting::FormattingSettings,
+) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.formatting = formatting;
+    AppSettings::set(&app, &settings)
 }
 
+/// The built-in commands for `language` merged with the user's own, as they
+/// will be applied.
 #[tauri::command]
 #[specta::specta]
-fn get_selected_model(app: tauri::AppHandle) -> Result<Option<String>, String> {
+fn get_formatting_commands(
+    app: tauri::AppHandle,
+    language: String,
+) -> Result<formatting::LanguageCommands, String> {
     let settings = AppSettings::get_or_default(&app);
-    Ok(settings.selected_model)
+    Ok(formatting::commands_for(&language, &settings.formatting))
 }
 
 #[tauri::command]
 #[specta::specta]
-async fn set_selected_model(
-    app: tauri::AppHandle,
-    whisper_model: State<'_, Arc<Mutex<WhisperModel>>>,
-    model_id: String,
-) -> Result<(), String> {
-    if whisper::WhisperModelInfo::get_by_id(&model_id).is_none() {
-        return Err(format!("Invalid model ID: {}", model_id));
-    }
+fn get_itn_settings(app: tauri::AppHandle) -> Result<itn::ItnSettings, String> {
+    Ok(AppSettings::get_or_default(&app).itn)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_itn_settings(app: tauri::AppHandle, itn: itn::ItnSettings) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.itn = itn;
+    AppSettings::set(&app, &settings)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_editing_settings(app: tauri::AppHandle) -> Result<editing::EditingSettings, String> {
+    Ok(AppSettings::get_or_default(&app).editing)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_editing_settings(app: tauri::AppHandle, editing: editing::EditingSettings) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.editing = editing;
+    AppSettings::set(&app, &settings)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_profiles(app: tauri::AppHandle) -> Result<Vec<profiles::Profile>, String> {
+    Ok(AppSettings::get_or_default(&app).profiles)
+}
+
+/// Adds `profile`, or replaces the profile with the same ID. An empty ID creates a new one.
+#[tauri::command]
+#[specta::specta]
+fn save_profile(app: tauri::AppHandle, mut profile: profiles::Profile) -> Result<profiles::Profile, String> {
+    profile.validate()?;
     
     let mut settings = AppSettings::get_or_default(&app);
-    settings.selected_model = Some(model_id.clone());
+    match settings.profiles.iter_mut().find(|p| !profile.id.is_empty() && p.id == profile.id) {
+        Some(existing) => *existing = profile.clone(),
+        None => {
+            profile.id = uuid::Uuid::new_v4().to_string();
+            settings.profiles.push(profile.clone());
+        }
+    }
     AppSettings::set(&app, &settings)?;
+    Ok(profile)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn delete_profile(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.profiles.retain(|p| p.id != id);
+    if settings.profile_override.as_deref() == Some(id.as_str()) {
+        settings.profile_override = None;
+    }
+    AppSettings::set(&app, &settings)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_profile_override(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    Ok(AppSettings::get_or_default(&app).profile_override)
+}
+
+/// Forces every recording onto one profile. `None` goes back to picking by focused app.
+#[tauri::command]
+#[specta::specta]
+fn set_profile_override(app: tauri::AppHandle, profile_id: Option<String>) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    if let Some(id) = &profile_id {
+        if !settings.profiles.iter().any(|p| &p.id == id) {
+            return Err("Profile not found".to_string());
+        }
+    }
+    settings.profile_override = profile_id;
+    AppSettings::set(&app, &settings)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_rewrite_settings(app: tauri::AppHandle) -> Result<rewrite::RewriteSettings, String> {
+    Ok(AppSettings::get_or_default(&app).rewrite)
+}
+
+/// Saves the endpoint and prompts, giving new prompts an ID, and re-registers
+/// prompt hotkeys.
+#[tauri::command]
+#[specta::specta]
+fn set_rewrite_settings(app: tauri::AppHandle, mut rewrite: rewrite::RewriteSettings) -> Result<rewrite::RewriteSettings, String> {
+    rewrite.endpoint.validate()?;
     
-    if WhisperModel::is_downloaded(&model_id) {
-        let mut model = whisper_model.lock().unwrap();
-        model.load_model(Some(model_id))?;
+    let mut settings = AppSettings::get_or_default(&app);
+    let mut used_keys: Vec<&str> = [&settings.hotkey, &settings.command_hotkey]
+        .into_iter()
+        .filter_map(|h| h.as_deref().and_then(shortcut_key))
+        .collect();
+    for prompt in &mut rewrite.prompts {
+        if prompt.name.trim().is_empty() || prompt.system_prompt.trim().is_empty() {
+            return Err("Rewrite prompts need a name and a system prompt".to_string());
+        }
+        if prompt.id.is_empty() {
+            prompt.id = uuid::Uuid::new_v4().to_string();
+        }
+        if let Some(hotkey) = &prompt.hotkey {
+            let key = shortcut_key(hotkey).ok_or_else(|| format!("Unsupported hotkey: {}", hotkey))?;
+            if used_keys.contains(&key) {
+                return Err(format!("{} is already used by another hotkey", hotkey));
+            }
+            used_keys.push(key);
+        }
+    }
+    if let Some(id) = &rewrite.default_prompt_id {
+        if rewrite.prompt(id).is_none() {
+            return Err("Default rewrite prompt not found".to_string());
+        }
     }
     
-    Ok(())
+    settings.rewrite = rewrite.clone();
+    AppSettings::set(&app, &settings)?;
+    register_secondary_hotkeys(&app)?;
+    Ok(rewrite)
 }
 
 #[tauri::command]
 #[specta::specta]
-fn get_model_path(app: tauri::AppHandle) -> Result<String, String> {
-    let settings = AppSettings::get_or_default(&app);
-    if let Some(model_id) = settings.selected_model {
-        if let Some(model_info) = whisper::WhisperModelInfo::get_by_id(&model_id) {
-            WhisperModel::get_model_path(&model_info.filename)
-                .map(|p| p.to_string_lossy().to_string())
-        } else {
-            Err("Invalid model ID".to_string())
+fn get_command_hotkey(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    Ok(AppSettings::get_or_default(&app).command_hotkey)
+}
+
+/// Sets the hotkey for voice commands on the selected text. `None` turns them off.
+#[tauri::command]
+#[specta::specta]
+fn set_command_hotkey(app: tauri::AppHandle, hotkey: Option<String>) -> Result<(), String> {
+    let mut settings = AppSettings::get_or_default(&app);
+    if let Some(hotkey) = &hotkey {
+        if !command_mode::SUPPORTED {
+            return Err("Voice commands are not supported on this platform".to_string());
+        }
+        let key = shortcut_key(hotkey).ok_or_else(|| format!("Unsupported hotkey: {}", hotkey))?;
+        let taken = settings.hotkey.iter()
+            .chain(settings.rewrite.prompts.iter().filter_map(|p| p.hotkey.as_ref()))
+            .any(|h| shortcut_key(h) == Some(key));
+        if taken {
+            return Err(format!("{} is already used by another hotkey", hotkey));
         }
-    } else {
-        Err("No model selected".to_string())
     }
+    settings.command_hotkey = hotkey;
+    AppSettings::set(&app, &settings)?;
+    register_secondary_hotkeys(&app)
 }
 
 #[tauri::command]
 #[specta::specta]
-fn set_groq_api_key(app: tauri::AppHandle, key: String) -> Result<(), String> {
-    let store = app.store("settings.json").map_err(|e| e.to_string())?;
-    store.set("groq_api_key", serde_json::Value::String(key));
-    store.save().map_err(|e| e.to_string())
+fn set_rewrite_api_key(app: tauri::AppHandle, key: String) -> Result<(), String> {
+    rewrite::set_api_key(&app, key)
 }
 
 #[tauri::command]
 #[specta::specta]
-fn clear_groq_api_key(app: tauri::AppHandle) -> Result<(), String> {
-    let store = app.store("settings.json").map_err(|e| e.to_string())?;
-    store.delete("groq_api_key");
-    store.save().map_err(|e| e.to_string())
+fn clear_rewrite_api_key(app: tauri::AppHandle) -> Result<(), String> {
+    rewrite::clear_api_key(&app)
 }
 
 #[tauri::command]
 #[specta::specta]
-fn has_groq_api_key(app: tauri::AppHandle) -> Result<bool, String> {
-    let store = app.store("settings.json").map_err(|e| e.to_string())?;
-    let exists = match store.get("groq_api_key") {
-        Some(val) => val.as_str().is_some(),
-        None => false,
-    };
-    Ok(exists)
+fn has_rewrite_api_key(app: tauri::AppHandle) -> Result<bool, String> {
+    Ok(rewrite::has_api_key(&app))
+}
+
+/// The profile and source app of the latest recording, so the app it was
+/// dictated into can be added to a profile.
+#[tauri::command]
+#[specta::specta]
+fn get_dictation_context(state: State<'_, profiles::ProfileState>) -> Result<profiles::DictationContext, String> {
+    Ok(state.current())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_transcription_backends(
+    app: tauri::AppHandle,
+    registry: State<'_, backend::BackendRegistry>,
+) -> Result<Vec<backend::BackendInfo>, String> {
+    Ok(registry.list(&app))
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_active_backend(app: tauri::AppHandle, registry: State<'_, backend::BackendRegistry>) -> Result<String, String> {
+    Ok(registry.active(&app).id())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_active_backend(
+    app: tauri::AppHandle,
+    registry: State<'_, backend::BackendRegistry>,
+    backend_id: String,
+) -> Result<(), String> {
+    if registry.get(&app, &backend_id).is_none() {
+        return Err(format!("Unknown transcription backend: {}", backend_id));
+    }
+    
+    let mut settings = AppSettings::get_or_default(&app);
+    settings.active_backend = Some(backend_id);
+    AppSettings::set(&app, &settings)
+}
+
+pub(crate) fn context() -> tauri::Context<tauri::Wry> {
+    tauri::generate_context!()
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -1072,7 +2059,26 @@ pub fn run() {
             get_transcripts,
             get_transcript_stats,
             delete_transcript,
+            update_transcript_text,
+            export_transcripts,
             clear_all_transcripts,
+            get_snippets,
+            create_snippet,
+            update_snippet,
+            delete_snippet,
+            get_replacement_rules,
+            save_replacement_rule,
+            delete_replacement_rule,
+            reset_replacement_hit_counts,
+            export_replacement_rules_csv,
+            import_replacement_rules_csv,
+            get_vocabulary,
+            set_vocabulary,
+            get_correction_settings,
+            set_correction_settings,
+            get_correction_suggestions,
+            accept_correction_suggestion,
+            dismiss_correction_suggestion,
             is_fn_key_pressed,
             test_fn_key,
             check_model_downloaded,
@@ -1080,13 +2086,61 @@ pub fn run() {
             get_model_path,
             get_available_models,
             get_downloaded_models,
+            benchmark_models,
+            get_benchmark_results,
+            import_model_bundle,
+            export_model_bundle,
             get_selected_model,
             set_selected_model,
+            get_two_pass_settings,
+            set_two_pass_settings,
             set_groq_api_key,
             clear_groq_api_key,
             has_groq_api_key,
+            get_cloud_providers,
+            save_cloud_provider,
+            delete_cloud_provider,
+            set_provider_api_key,
+            clear_provider_api_key,
+            has_provider_api_key,
+            get_usage_summary,
+            get_usage_limits,
+            set_usage_limits,
+            get_privacy_mode,
+            set_privacy_mode,
+            get_egress_audit_log,
+            get_network_settings,
+            set_network_settings,
+            get_post_processing_settings,
+            set_post_processing_settings,
+            get_formatting_settings,
+            set_formatting_settings,
+            get_formatting_commands,
+            get_itn_settings,
+            set_itn_settings,
+            get_editing_settings,
+            set_editing_settings,
+            get_profiles,
+            save_profile,
+            delete_profile,
+            get_profile_override,
+            set_profile_override,
+            get_dictation_context,
+            get_rewrite_settings,
+            get_command_hotkey,
+            set_command_hotkey,
+            set_rewrite_settings,
+            set_rewrite_api_key,
+            clear_rewrite_api_key,
+            has_rewrite_api_key,
+            get_transcription_backends,
+            get_active_backend,
+            set_active_backend,
             get_whisper_settings,
-            set_whisper_settings
+            set_whisper_settings,
+            get_context_carryover_settings,
+            set_context_carryover_settings,
+            reset_dictation_context
         ])
         .events(collect_events![
             TranscriptionProgress,
@@ -1096,8 +2150,12 @@ pub fn run() {
             FnKeyStateChanged,
             AudioLevelUpdate,
             RecordingStatsUpdated,
+            TranscriptRefined,
             whisper::ModelDownloadProgress,
-            whisper::ModelDownloadComplete
+            whisper::ModelDownloadComplete,
+            benchmark::BenchmarkProgress,
+            backend::BackendFallback,
+            profiles::ProfileSelected
         ]);
     
     #[cfg(debug_assertions)]
@@ -1120,6 +2178,10 @@ pub fn run() {
         .manage(fn_listener.clone())
         .manage(bubble_task_state)
         .manage(Arc::new(Mutex::new(whisper_model)))
+        .manage(backend::BackendRegistry::default())
+        .manage(editing::InsertionHistory::default())
+        .manage(profiles::ProfileState::new(profiles::default_provider()))
+        .manage(command_mode::CommandState::default())
         .invoke_handler(builder.invoke_handler())
         .setup(move |app| {
             builder.mount_events(app);
@@ -1131,6 +2193,20 @@ pub fn run() {
                 audio_manager.set_app_handle(app_handle).await;
             });
             
+            let secret_store = secrets::SecretStore::open(app.handle()).unwrap_or_else(|e| {
+                eprintln!("❌ Failed to open secret store, keeping API keys in memory for this session: {}", e);
+                let _ = app
+                    .notification()
+                    .builder()
+                    .title("Saved API keys unavailable")
+                    .body(format!("TalkType couldn't open its secret store, so saved API keys can't be used and new ones are only kept until you quit.\n{}", e))
+                    .show();
+                secrets::SecretStore::in_memory()
+            });
+            app.manage(secret_store);
+            secrets::migrate_plaintext_secrets(app.handle());
+            backend::migrate_active_backend(app.handle());
+            
             let settings = AppSettings::get_or_default(&app.handle());
             if let Some(model_id) = settings.selected_model {
                 if WhisperModel::is_downloaded(&model_id) {
@@ -1148,6 +2224,17 @@ pub fn run() {
                 println!("⚠️ No Whisper model selected");
             }
             
+            if let Some(draft_id) = settings.draft_model.filter(|_| settings.two_pass_enabled) {
+                if WhisperModel::is_downloaded(&draft_id) {
+                    println!("🔄 Loading draft Whisper model: {}...", draft_id);
+                    let whisper_state = app.state::<Arc<Mutex<WhisperModel>>>();
+                    let mut model = whisper_state.lock().unwrap();
+                    if let Err(e) = model.load_draft_model(Some(draft_id)) {
+                        println!("❌ Failed to load draft model: {}", e);
+                    }
+                }
+            }
+            
             tray::create_tray(&app.handle())?;
             
             let window = window::create_main_window(&app.handle())?;
@@ -1194,6 +2281,7 @@ pub fn run() {
                             let audio_manager = app_handle_fn.state::<Arc<AudioManager>>().inner().clone();
                             if payload.is_pressed {
                                 println!("🎤 Fn key pressed - Starting recording");
+                                begin_dictation(&app_handle_fn, None);
                                 sound::play_start_sound(&app_handle_fn);
                                 HotkeyPressed { pressed: true }.emit(&app_handle_fn).ok();
                                 RecordingStateChanged { is_recording: true }.emit(&app_handle_fn).ok();
@@ -1234,21 +2322,14 @@ pub fn run() {
                                 tauri::async_runtime::spawn(async move {
                                     match app_handle_clone.try_state::<Arc<AudioManager>>() {
                                         Some(audio_state) => {
-                                            match app_handle_clone.try_state::<Arc<Mutex<WhisperModel>>>() {
-                                                Some(whisper_state) => {
-                                                    match stop_recording_chunked(app_handle_clone.clone(), audio_state, whisper_state).await {
-                                                        Ok(text) => {
-                                                            if !text.is_empty() {
-                                                                let _ = insert_text_at_cursor(text);
-                                                            }
-                                                        }
-                                                        Err(e) => {
-                                                            eprintln!("Failed to transcribe: {}", e);
-                                                        }
+                                            match stop_recording_chunked(app_handle_clone.clone(), audio_state).await {
+                                                Ok(text) => {
+                                                    if !text.is_empty() {
+                                                        let _ = insert_text_at_cursor(app_handle_clone.clone(), text);
                                                     }
                                                 }
-                                                None => {
-                                                    eprintln!("Failed to get whisper model state");
+                                                Err(e) => {
+                                                    eprintln!("Failed to transcribe: {}", e);
                                                 }
                                             }
                                         }
@@ -1264,6 +2345,9 @@ pub fn run() {
             });
             
             let _ = window::create_bubble_window(app.handle());
+            if let Err(e) = register_secondary_hotkeys(app.handle()) {
+                eprintln!("Failed to register rewrite prompt hotkeys: {}", e);
+            }
             #[cfg(target_os = "macos")]
             {
                 window::start_dock_monitor(&app.handle());
@@ -1275,7 +2359,7 @@ pub fn run() {
             if let tauri::WindowEvent::CloseRequested { .. } = event {
             }
         })
-        .build(tauri::generate_context!())
+        .build(context())
         .expect("error while running tauri application")
         .run(move |app_handle, event| match event {
             #[cfg(target_os = "macos")]
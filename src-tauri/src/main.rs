@@ -1,5 +1,14 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--eval") {
+        if let Err(e) = talktype_lib::eval::run_cli(&args) {
+            eprintln!("❌ Evaluation failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     talktype_lib::run()
 }
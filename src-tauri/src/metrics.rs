@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct ErrorRate {
+    pub errors: u32,
+    pub reference_len: u32,
+    pub rate: f32,
+}
+
+impl ErrorRate {
+    fn new(errors: usize, reference_len: usize) -> Self {
+        let rate = if reference_len > 0 {
+            errors as f32 / reference_len as f32
+        } else if errors > 0 {
+            1.0
+        } else {
+            0.0
+        };
+        Self {
+            errors: errors as u32,
+            reference_len: reference_len as u32,
+            rate,
+        }
+    }
+
+    /// Combines per-clip counts so the overall rate is weighted by reference length.
+    pub fn sum<'a>(rates: impl IntoIterator<Item = &'a ErrorRate>) -> Self {
+        let (errors, reference_len) = rates
+            .into_iter()
+            .fold((0usize, 0usize), |(e, n), r| (e + r.errors as usize, n + r.reference_len as usize));
+        Self::new(errors, reference_len)
+    }
+}
+
+/// Lowercases and strips punctuation so scoring only reflects the spoken words.
+pub fn normalize_for_scoring(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '\'' {
+                c.to_lowercase().next().unwrap_or(c)
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn word_error_rate(reference: &str, hypothesis: &str) -> ErrorRate {
+    let reference = normalize_for_scoring(reference);
+    let hypothesis = normalize_for_scoring(hypothesis);
+    let ref_words: Vec<&str> = reference.split_whitespace().collect();
+    let hyp_words: Vec<&str> = hypothesis.split_whitespace().collect();
+    ErrorRate::new(edit_distance(&ref_words, &hyp_words), ref_words.len())
+}
+
+pub fn char_error_rate(reference: &str, hypothesis: &str) -> ErrorRate {
+    let ref_chars: Vec<char> = normalize_for_scoring(reference).chars().collect();
+    let hyp_chars: Vec<char> = normalize_for_scoring(hypothesis).chars().collect();
+    ErrorRate::new(edit_distance(&ref_chars, &hyp_chars), ref_chars.len())
+}
+
+fn edit_distance<T: PartialEq>(reference: &[T], hypothesis: &[T]) -> usize {
+    let mut prev: Vec<usize> = (0..=hypothesis.len()).collect();
+    let mut curr = vec![0usize; hypothesis.len() + 1];
+
+    for (i, r) in reference.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, h) in hypothesis.iter().enumerate() {
+            let substitution = prev[j] + if r == h { 0 } else { 1 };
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[hypothesis.len()]
+}
@@ -0,0 +1,277 @@
+use crate::whisper::{WhisperModel, WhisperModelInfo};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_FILE: &str = "talktype-models.json";
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct BundleModel {
+    pub id: String,
+    pub filename: String,
+    pub size_bytes: f64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub created_at: f64, // Unix timestamp in milliseconds
+    pub models: Vec<BundleModel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct BundleImportResult {
+    pub installed: Vec<String>,
+    pub already_present: Vec<String>,
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn is_archive(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    name.ends_with(".tar") || name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+/// Unpacks only the manifest and files named like registry models, so an
+/// archive can't drop anything else into the model directory.
+fn extract_archive(archive_path: &Path, dest: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive_path)
+        .map_err(|e| format!("Failed to open {}: {}", archive_path.display(), e))?;
+    let name = archive_path.to_string_lossy().to_lowercase();
+    let reader: Box<dyn Read> = if name.ends_with(".gz") || name.ends_with(".tgz") {
+        Box::new(flate2::read::GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+
+    let known_files: Vec<String> = WhisperModelInfo::all()
+        .into_iter()
+        .map(|m| m.filename)
+        .chain(std::iter::once(MANIFEST_FILE.to_string()))
+        .collect();
+
+    let mut archive = tar::Archive::new(reader);
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        let file_name = entry
+            .path()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        if !entry.header().entry_type().is_file() || !known_files.contains(&file_name) {
+            continue;
+        }
+        entry
+            .unpack(dest.join(&file_name))
+            .map_err(|e| format!("Failed to extract {}: {}", file_name, e))?;
+    }
+    Ok(())
+}
+
+/// Checks every manifest entry against the registry and the files on disk
+/// before anything is installed.
+fn validate(manifest: &BundleManifest, source_dir: &Path) -> Result<(), String> {
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!(
+            "Bundle manifest version {} is newer than this app supports",
+            manifest.version
+        ));
+    }
+    if manifest.models.is_empty() {
+        return Err("Bundle manifest lists no models".to_string());
+    }
+
+    for model in &manifest.models {
+        let info = WhisperModelInfo::get_by_id(&model.id)
+            .ok_or_else(|| format!("Unknown model in bundle: {}", model.id))?;
+        if info.filename != model.filename {
+            return Err(format!(
+                "Model {} should be named {}, not {}",
+                model.id, info.filename, model.filename
+            ));
+        }
+
+        let path = source_dir.join(&model.filename);
+        let size = fs::metadata(&path)
+            .map_err(|_| format!("Bundle is missing {}", model.filename))?
+            .len();
+        if size as f64 != model.size_bytes {
+            return Err(format!(
+                "{} is {} bytes, manifest says {}",
+                model.filename, size, model.size_bytes
+            ));
+        }
+
+        println!("🔎 Verifying {}", model.filename);
+        let hash = sha256_file(&path)?;
+        if !hash.eq_ignore_ascii_case(&model.sha256) {
+            return Err(format!("Checksum mismatch for {}", model.filename));
+        }
+    }
+    Ok(())
+}
+
+/// Installs models from a directory or `.tar`/`.tar.gz` archive that contains
+/// a `talktype-models.json` manifest. Nothing is installed unless every model
+/// in the bundle checks out.
+pub fn import_bundle(source: &Path) -> Result<BundleImportResult, String> {
+    let model_dir = WhisperModel::get_model_dir()?;
+
+    let staging_dir = if is_archive(source) {
+        let dir = model_dir.join(format!(".import-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create staging dir: {}", e))?;
+        if let Err(e) = extract_archive(source, &dir) {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(e);
+        }
+        Some(dir)
+    } else if source.is_dir() {
+        None
+    } else {
+        return Err(format!("{} is not a directory or .tar/.tar.gz archive", source.display()));
+    };
+    let source_dir = staging_dir.as_deref().unwrap_or(source);
+
+    let result = install_from_dir(source_dir, &model_dir, staging_dir.is_some());
+    if let Some(dir) = staging_dir {
+        let _ = fs::remove_dir_all(dir);
+    }
+    result
+}
+
+fn install_from_dir(source_dir: &Path, model_dir: &Path, can_move: bool) -> Result<BundleImportResult, String> {
+    let manifest_path = source_dir.join(MANIFEST_FILE);
+    let data = fs::read_to_string(&manifest_path)
+        .map_err(|_| format!("Bundle has no {}", MANIFEST_FILE))?;
+    let manifest: BundleManifest = serde_json::from_str(&data)
+        .map_err(|e| format!("Failed to parse bundle manifest: {}", e))?;
+
+    validate(&manifest, source_dir)?;
+
+    let mut result = BundleImportResult {
+        installed: Vec::new(),
+        already_present: Vec::new(),
+    };
+    for model in &manifest.models {
+        if WhisperModel::is_downloaded(&model.id) {
+            result.already_present.push(model.id.clone());
+            continue;
+        }
+
+        let from = source_dir.join(&model.filename);
+        let to = model_dir.join(&model.filename);
+        let temp = to.with_extension("tmp");
+        // Files extracted into the staging dir live on the same volume and can
+        // just be renamed; anything else is copied first so a failed import
+        // never leaves a partial model behind.
+        if can_move {
+            fs::rename(&from, &to).map_err(|e| format!("Failed to install {}: {}", model.filename, e))?;
+        } else {
+            fs::copy(&from, &temp).map_err(|e| format!("Failed to copy {}: {}", model.filename, e))?;
+            fs::rename(&temp, &to).map_err(|e| format!("Failed to install {}: {}", model.filename, e))?;
+        }
+        println!("📦 Installed {}", model.id);
+        result.installed.push(model.id.clone());
+    }
+    Ok(result)
+}
+
+/// Packages every downloaded model, plus a manifest, into a `.tar` archive at
+/// `destination`. Model weights barely compress, so the archive isn't gzipped.
+pub fn export_bundle(destination: &Path, model_ids: Option<&[String]>) -> Result<BundleManifest, String> {
+    let model_ids: Vec<String> = match model_ids {
+        Some(ids) => ids.to_vec(),
+        None => WhisperModel::get_downloaded_models(),
+    };
+    if model_ids.is_empty() {
+        return Err("No downloaded models to export".to_string());
+    }
+
+    let mut models = Vec::new();
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for id in &model_ids {
+        let info = WhisperModelInfo::get_by_id(id).ok_or_else(|| format!("Unknown model: {}", id))?;
+        let path = WhisperModel::get_model_path(&info.filename)?;
+        if !path.is_file() {
+            return Err(format!("Model {} is not downloaded", id));
+        }
+
+        println!("🔎 Hashing {}", info.filename);
+        let size = fs::metadata(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+            .len();
+        models.push(BundleModel {
+            id: id.clone(),
+            filename: info.filename,
+            size_bytes: size as f64,
+            sha256: sha256_file(&path)?,
+        });
+        paths.push(path);
+    }
+
+    let manifest = BundleManifest {
+        version: MANIFEST_VERSION,
+        created_at: chrono::Utc::now().timestamp_millis() as f64,
+        models,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize bundle manifest: {}", e))?;
+
+    let temp = destination.with_extension("tmp");
+    let file = fs::File::create(&temp)
+        .map_err(|e| format!("Failed to create {}: {}", temp.display(), e))?;
+    let mut builder = tar::Builder::new(file);
+
+    let write = |builder: &mut tar::Builder<fs::File>| -> std::io::Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest_json.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime((manifest.created_at / 1000.0) as u64);
+        header.set_cksum();
+        builder.append_data(&mut header, MANIFEST_FILE, manifest_json.as_slice())?;
+
+        for (model, path) in manifest.models.iter().zip(&paths) {
+            println!("📦 Adding {}", model.filename);
+            builder.append_path_with_name(path, &model.filename)?;
+        }
+        builder.finish()
+    };
+    if let Err(e) = write(&mut builder) {
+        drop(builder);
+        let _ = fs::remove_file(&temp);
+        return Err(format!("Failed to write bundle: {}", e));
+    }
+    drop(builder);
+
+    fs::rename(&temp, destination).map_err(|e| format!("Failed to write bundle: {}", e))?;
+    Ok(manifest)
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, Runtime};
+
+const AUDIT_LOG_FILE: &str = "egress-audit.log";
+
+/// One line of the egress audit log. Only the host is recorded, never the
+/// payload or credentials.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct EgressAttempt {
+    pub timestamp: f64, // Unix timestamp in milliseconds
+    pub purpose: String,
+    pub host: String,
+    pub allowed: bool,
+}
+
+fn audit_log_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let app_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    if !app_dir.exists() {
+        std::fs::create_dir_all(&app_dir)
+            .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+
+    Ok(app_dir.join(AUDIT_LOG_FILE))
+}
+
+fn append_audit_log<R: Runtime>(app: &AppHandle<R>, attempt: &EgressAttempt) -> Result<(), String> {
+    let line = serde_json::to_string(attempt).map_err(|e| e.to_string())?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(audit_log_path(app)?)
+        .map_err(|e| format!("Failed to open audit log: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write audit log: {}", e))
+}
+
+/// Gate for every code path that talks to the network. Each attempt is written
+/// to the audit log; in privacy mode it is refused.
+pub fn check_egress<R: Runtime>(app: &AppHandle<R>, purpose: &str, url: &str) -> Result<(), String> {
+    let privacy_mode = crate::AppSettings::get_or_default(app).privacy_mode;
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_string()))
+        .unwrap_or_else(|| url.to_string());
+
+    let attempt = EgressAttempt {
+        timestamp: chrono::Utc::now().timestamp_millis() as f64,
+        purpose: purpose.to_string(),
+        host,
+        allowed: !privacy_mode,
+    };
+    if let Err(e) = append_audit_log(app, &attempt) {
+        eprintln!("{}", e);
+    }
+
+    if privacy_mode {
+        println!("🔒 Blocked network access to {} ({})", attempt.host, purpose);
+        return Err(format!(
+            "Privacy mode is on: refusing to contact {} for {}. Turn off privacy mode to allow network access.",
+            attempt.host, purpose
+        ));
+    }
+    Ok(())
+}
+
+/// Most recent attempts first.
+pub fn read_audit_log<R: Runtime>(app: &AppHandle<R>, limit: Option<u32>) -> Result<Vec<EgressAttempt>, String> {
+    let path = audit_log_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let data = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read audit log: {}", e))?;
+    let attempts = data
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok());
+
+    Ok(match limit {
+        Some(n) => attempts.take(n as usize).collect(),
+        None => attempts.collect(),
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct NetworkSettings {
+    /// e.g. `http://proxy.corp:3128`. When unset, `HTTPS_PROXY`/`HTTP_PROXY`/`ALL_PROXY` apply.
+    pub proxy_url: Option<String>,
+    /// Comma-separated hosts that bypass `proxy_url`. Falls back to `NO_PROXY`.
+    pub no_proxy: Option<String>,
+    /// PEM file with extra root certificates, trusted alongside the system ones.
+    /// Falls back to `TALKTYPE_CA_BUNDLE`.
+    pub ca_bundle_path: Option<String>,
+    /// Falls back to `TALKTYPE_USER_AGENT`, then `TalkType/<version>`.
+    pub user_agent: Option<String>,
+}
+
+fn setting_or_env(value: &Option<String>, vars: &[&str]) -> Option<String> {
+    value
+        .clone()
+        .or_else(|| vars.iter().find_map(|var| std::env::var(var).ok()))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn load_ca_bundle(path: &str) -> Result<Vec<reqwest::Certificate>, String> {
+    const END_MARKER: &str = "-----END CERTIFICATE-----";
+
+    let pem = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read CA bundle {}: {}", path, e))?;
+    let certs = pem
+        .split_inclusive(END_MARKER)
+        .filter(|block| block.contains(END_MARKER))
+        .map(|block| {
+            reqwest::Certificate::from_pem(block.trim().as_bytes())
+                .map_err(|e| format!("Invalid certificate in {}: {}", path, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if certs.is_empty() {
+        return Err(format!("No certificates found in {}", path));
+    }
+    Ok(certs)
+}
+
+/// Builder with the proxy, CA and user-agent configuration applied; callers add
+/// their own timeouts. Every HTTP request the app makes should start here.
+pub fn client_builder(settings: &NetworkSettings) -> Result<reqwest::ClientBuilder, String> {
+    let user_agent = setting_or_env(&settings.user_agent, &["TALKTYPE_USER_AGENT"])
+        .unwrap_or_else(|| format!("TalkType/{}", env!("CARGO_PKG_VERSION")));
+    let mut builder = reqwest::Client::builder().user_agent(user_agent);
+
+    // Without an explicit proxy, reqwest already honours the proxy variables
+    // (including NO_PROXY) from the environment.
+    if let Some(proxy_url) = settings.proxy_url.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        let no_proxy = setting_or_env(&settings.no_proxy, &["NO_PROXY", "no_proxy"])
+            .and_then(|hosts| reqwest::NoProxy::from_string(&hosts));
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| format!("Invalid proxy URL {}: {}", proxy_url, e))?
+            .no_proxy(no_proxy);
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = setting_or_env(&settings.ca_bundle_path, &["TALKTYPE_CA_BUNDLE"]) {
+        for cert in load_ca_bundle(&path)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    Ok(builder)
+}
+
+pub fn http_client<R: Runtime>(app: &AppHandle<R>) -> Result<reqwest::Client, String> {
+    client_builder(&crate::AppSettings::get_or_default(app).network)?
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
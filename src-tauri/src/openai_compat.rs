@@ -0,0 +1,979 @@
+use reqwest::multipart::{Form, Part};
+use rubato::{FftFixedInOut, Resampler};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::time::Duration;
+use crate::net::{self, NetworkSettings};
+use crate::pipeline;
+use crate::secrets::SecretStore;
+use crate::transcripts::TranscriptSegment;
+use crate::whisper::TranscriptionOutput;
+use tauri::{AppHandle, Manager};
+
+pub const GROQ_PROVIDER_ID: &str = "groq";
+// Kept from when Groq was the only cloud option so existing keys keep working.
+pub const GROQ_STORE_KEY: &str = "groq_api_key";
+
+// Groq and OpenAI both reject uploads over 25 MB; leave room for the multipart overhead.
+const DEFAULT_MAX_UPLOAD_MB: u32 = 20;
+const UPLOAD_SAMPLE_RATE: u32 = 16_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthScheme {
+    /// `Authorization: Bearer <key>`, used by Groq and OpenAI.
+    Bearer,
+    /// The key is sent verbatim in a custom header, e.g. `X-Api-Key`.
+    Header { name: String },
+    /// No credentials, typically a server on the local network.
+    None,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, specta::Type)]
+pub enum UploadFormat {
+    Wav,
+    #[default]
+    Flac, // Lossless and roughly half the size of WAV for speech
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ProviderConfig {
+    pub id: String,
+    pub name: String,
+    /// Base of the OpenAI-style API, e.g. `https://api.openai.com/v1`.
+    pub base_url: String,
+    pub model: String,
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
+    pub auth: AuthScheme,
+    #[serde(default)]
+    pub upload_format: UploadFormat,
+    /// Uploads larger than this are split at silence and sent in parallel.
+    #[serde(default)]
+    pub max_upload_mb: Option<u32>,
+    /// Used for spend estimates only; providers bill per audio hour.
+    #[serde(default)]
+    pub cost_per_hour_usd: Option<f64>,
+}
+
+impl ProviderConfig {
+    pub fn groq() -> Self {
+        Self {
+            id: GROQ_PROVIDER_ID.to_string(),
+            name: "Groq".to_string(),
+            base_url: "https://api.groq.com/openai/v1".to_string(),
+            model: "whisper-large-v3".to_string(),
+            extra_headers: HashMap::new(),
+            auth: AuthScheme::Bearer,
+            upload_format: UploadFormat::Flac,
+            max_upload_mb: Some(DEFAULT_MAX_UPLOAD_MB),
+            cost_per_hour_usd: Some(0.111),
+        }
+    }
+
+    pub fn openai() -> Self {
+        Self {
+            id: "openai".to_string(),
+            name: "OpenAI".to_string(),
+            base_url: "https://api.openai.com/v1".to_string(),
+            model: "whisper-1".to_string(),
+            extra_headers: HashMap::new(),
+            auth: AuthScheme::Bearer,
+            upload_format: UploadFormat::Flac,
+            max_upload_mb: Some(DEFAULT_MAX_UPLOAD_MB),
+            cost_per_hour_usd: Some(0.36),
+        }
+    }
+
+    pub fn transcriptions_url(&self) -> String {
+        format!("{}/audio/transcriptions", self.base_url.trim_end_matches('/'))
+    }
+
+    fn max_upload_bytes(&self) -> usize {
+        self.max_upload_mb.unwrap_or(DEFAULT_MAX_UPLOAD_MB).max(1) as usize * 1024 * 1024
+    }
+
+    pub fn requires_api_key(&self) -> bool {
+        self.auth != AuthScheme::None
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let id_ok = !self.id.is_empty()
+            && self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !id_ok {
+            return Err("Provider ID may only contain letters, digits, '-' and '_'".to_string());
+        }
+        if self.id == crate::backend::LOCAL_BACKEND_ID {
+            return Err(format!("'{}' is reserved for local Whisper", self.id));
+        }
+        if self.model.trim().is_empty() {
+            return Err("Provider model must not be empty".to_string());
+        }
+        let url = reqwest::Url::parse(&self.base_url)
+            .map_err(|e| format!("Invalid base URL '{}': {}", self.base_url, e))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(format!("Unsupported URL scheme: {}", url.scheme()));
+        }
+        if let AuthScheme::Header { name } = &self.auth {
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid auth header name: {}", name))?;
+        }
+        for (name, value) in &self.extra_headers {
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid header name: {}", name))?;
+            reqwest::header::HeaderValue::from_str(value)
+                .map_err(|_| format!("Invalid value for header {}", name))?;
+        }
+        Ok(())
+    }
+}
+
+/// Providers saved in settings, with the Groq preset always present so an
+/// existing Groq setup keeps working.
+pub fn providers(app: &AppHandle) -> Vec<ProviderConfig> {
+    let mut providers = crate::AppSettings::get_or_default(app).cloud_providers;
+    if !providers.iter().any(|p| p.id == GROQ_PROVIDER_ID) {
+        providers.insert(0, ProviderConfig::groq());
+    }
+    providers
+}
+
+fn api_key_secret_name(provider_id: &str) -> String {
+    if provider_id == GROQ_PROVIDER_ID {
+        GROQ_STORE_KEY.to_string()
+    } else {
+        format!("provider_api_key:{}", provider_id)
+    }
+}
+
+/// Secret names for every configured provider's key.
+pub fn api_key_secret_names(app: &AppHandle) -> Vec<String> {
+    providers(app).iter().map(|p| api_key_secret_name(&p.id)).collect()
+}
+
+pub fn get_api_key(app: &AppHandle, provider_id: &str) -> Option<String> {
+    app.state::<SecretStore>()
+        .get(&api_key_secret_name(provider_id))
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to read API key: {}", e);
+            None
+        })
+}
+
+/// Checks for a key without reading it out of the keychain or decrypting it.
+pub fn has_api_key(app: &AppHandle, provider_id: &str) -> bool {
+    app.state::<SecretStore>()
+        .contains(&api_key_secret_name(provider_id))
+}
+
+pub fn set_api_key(app: &AppHandle, provider_id: &str, key: String) -> Result<(), String> {
+    app.state::<SecretStore>()
+        .set(&api_key_secret_name(provider_id), &key)
+}
+
+pub fn clear_api_key(app: &AppHandle, provider_id: &str) -> Result<(), String> {
+    app.state::<SecretStore>()
+        .delete(&api_key_secret_name(provider_id))
+}
+
+#[derive(Deserialize, Serialize)]
+struct TranscriptionResponse {
+    text: String,
+    // The remaining fields are only present in `verbose_json` responses, and
+    // self-hosted servers don't always fill all of them in.
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    segments: Vec<ResponseSegment>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ResponseSegment {
+    start: f64, // Seconds
+    end: f64,
+    text: String,
+    #[serde(default)]
+    avg_logprob: Option<f32>,
+    #[serde(default)]
+    no_speech_prob: Option<f32>,
+}
+
+impl TranscriptionResponse {
+    fn into_output(self) -> TranscriptionOutput {
+        let segments = self
+            .segments
+            .into_iter()
+            .map(|segment| TranscriptSegment {
+                start_ms: segment.start * 1000.0,
+                end_ms: segment.end * 1000.0,
+                text: segment.text.trim().to_string(),
+                speaker: None,
+                speaker_turn_next: false,
+                avg_logprob: segment.avg_logprob,
+                no_speech_prob: segment.no_speech_prob,
+            })
+            .collect();
+
+        TranscriptionOutput {
+            text: self.text.trim().to_string(),
+            segments,
+            language: self.language.as_deref().and_then(normalize_language),
+        }
+    }
+}
+
+/// Providers report the language as a full name ("english") while local
+/// Whisper uses ISO codes ("en"); store the code either way.
+fn normalize_language(language: &str) -> Option<String> {
+    let language = language.trim().to_lowercase();
+    if language.is_empty() {
+        return None;
+    }
+    let code = whisper_rs::get_lang_id(&language)
+        .and_then(whisper_rs::get_lang_str)
+        .map(|code| code.to_string());
+    Some(code.unwrap_or(language))
+}
+
+/// Prepares audio the way the API expects it: 16 kHz mono with the DC offset
+/// removed and quiet recordings boosted.
+fn prepare_samples(audio_data: &[f32], sample_rate: u32) -> Result<Vec<f32>, String> {
+    let mut samples: Vec<f32> = if sample_rate != UPLOAD_SAMPLE_RATE {
+        let channels = 1;
+        let chunk_size = 1024;
+        let mut resampler =
+            FftFixedInOut::<f32>::new(sample_rate as usize, UPLOAD_SAMPLE_RATE as usize, chunk_size, channels)
+                .map_err(|e| e.to_string())?;
+        let mut output = Vec::new();
+        let mut input_pos = 0;
+        while input_pos < audio_data.len() {
+            let remaining = audio_data.len() - input_pos;
+            let take = remaining.min(chunk_size);
+            let mut in_buf = vec![vec![0.0f32; chunk_size]];
+            in_buf[0][..take].copy_from_slice(&audio_data[input_pos..input_pos + take]);
+            input_pos += take;
+            let mut out_buf = resampler.output_buffer_allocate(true);
+            resampler
+                .process_into_buffer(&in_buf, &mut out_buf, None)
+                .map_err(|e| e.to_string())?;
+            output.extend_from_slice(&out_buf[0]);
+        }
+        output
+    } else {
+        audio_data.to_vec()
+    };
+
+    let mean = if !samples.is_empty() {
+        samples.iter().copied().sum::<f32>() / samples.len() as f32
+    } else {
+        0.0
+    };
+    if mean.abs() > 1e-6 {
+        for s in &mut samples {
+            *s -= mean;
+        }
+    }
+    let mut peak = 0.0f32;
+    for s in &samples {
+        let a = s.abs();
+        if a > peak {
+            peak = a;
+        }
+    }
+    if peak > 0.0 && peak < 0.2 {
+        let mut gain = 0.8 / peak;
+        if gain > 4.0 {
+            gain = 4.0;
+        }
+        for s in &mut samples {
+            *s = (*s * gain).clamp(-1.0, 1.0);
+        }
+    }
+
+    Ok(samples)
+}
+
+struct Upload {
+    bytes: Vec<u8>,
+    file_name: &'static str,
+    mime: &'static str,
+}
+
+impl Upload {
+    fn encode(samples: &[f32], format: UploadFormat) -> Result<Self, String> {
+        let pcm = samples
+            .iter()
+            .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16);
+
+        match format {
+            UploadFormat::Wav => {
+                let mut cursor = Cursor::new(Vec::<u8>::new());
+                let spec = hound::WavSpec {
+                    channels: 1,
+                    sample_rate: UPLOAD_SAMPLE_RATE,
+                    bits_per_sample: 16,
+                    sample_format: hound::SampleFormat::Int,
+                };
+                {
+                    let mut writer = hound::WavWriter::new(&mut cursor, spec).map_err(|e| e.to_string())?;
+                    for v in pcm {
+                        writer.write_sample(v).map_err(|e| e.to_string())?;
+                    }
+                    writer.finalize().map_err(|e| e.to_string())?;
+                }
+                Ok(Self {
+                    bytes: cursor.into_inner(),
+                    file_name: "audio.wav",
+                    mime: "audio/wav",
+                })
+            }
+            UploadFormat::Flac => {
+                use flacenc::component::BitRepr;
+                use flacenc::error::Verify;
+
+                let pcm: Vec<i32> = pcm.map(i32::from).collect();
+                let config = flacenc::config::Encoder::default()
+                    .into_verified()
+                    .map_err(|(_, e)| format!("Invalid FLAC config: {:?}", e))?;
+                let source = flacenc::source::MemSource::from_samples(&pcm, 1, 16, UPLOAD_SAMPLE_RATE as usize);
+                let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+                    .map_err(|e| format!("FLAC encoding failed: {:?}", e))?;
+                let mut sink = flacenc::bitsink::ByteSink::new();
+                stream
+                    .write(&mut sink)
+                    .map_err(|e| format!("FLAC encoding failed: {:?}", e))?;
+                Ok(Self {
+                    bytes: sink.as_slice().to_vec(),
+                    file_name: "audio.flac",
+                    mime: "audio/flac",
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CloudError {
+    MissingApiKey(String),
+    Auth(String),
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    PayloadTooLarge(String),
+    Server { status: u16, message: String },
+    Request { status: u16, message: String },
+    Timeout,
+    Network(String),
+    InvalidResponse(String),
+    Encoding(String),
+}
+
+impl CloudError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            CloudError::RateLimited { .. }
+            | CloudError::Server { .. }
+            | CloudError::Timeout
+            | CloudError::Network(_) => true,
+            _ => false,
+        }
+    }
+
+    fn from_reqwest(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            CloudError::Timeout
+        } else {
+            CloudError::Network(e.to_string())
+        }
+    }
+
+    async fn from_response(res: reqwest::Response) -> Self {
+        let status = res.status();
+        let retry_after = res
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|secs| secs.is_finite() && *secs >= 0.0)
+            .map(Duration::from_secs_f64);
+
+        let body = res.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<ApiErrorBody>(&body)
+            .map(|b| b.error.message)
+            .unwrap_or_else(|_| {
+                let body = body.trim();
+                if body.is_empty() {
+                    status.canonical_reason().unwrap_or("Unknown error").to_string()
+                } else {
+                    body.chars().take(200).collect()
+                }
+            });
+
+        match status.as_u16() {
+            401 | 403 => CloudError::Auth(message),
+            413 => CloudError::PayloadTooLarge(message),
+            429 => CloudError::RateLimited { message, retry_after },
+            code if status.is_server_error() => CloudError::Server { status: code, message },
+            code => CloudError::Request { status: code, message },
+        }
+    }
+}
+
+impl std::fmt::Display for CloudError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CloudError::MissingApiKey(provider) => write!(f, "No API key configured for {}", provider),
+            CloudError::Auth(message) => write!(f, "Authentication failed: {}", message),
+            CloudError::RateLimited { message, .. } => write!(f, "Rate limited: {}", message),
+            CloudError::PayloadTooLarge(message) => write!(f, "Recording too large to upload: {}", message),
+            CloudError::Server { status, message } => write!(f, "Server error ({}): {}", status, message),
+            CloudError::Request { status, message } => write!(f, "Request rejected ({}): {}", status, message),
+            CloudError::Timeout => write!(f, "Request timed out"),
+            CloudError::Network(message) => write!(f, "Network error: {}", message),
+            CloudError::InvalidResponse(message) => write!(f, "Invalid response: {}", message),
+            CloudError::Encoding(message) => write!(f, "Failed to encode audio: {}", message),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiErrorBody {
+    error: ApiErrorDetail,
+}
+
+#[derive(Deserialize)]
+struct ApiErrorDetail {
+    message: String,
+}
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// reqwest 0.11 has no separate read timeout, so this bounds the whole upload
+// and response. Dictations are short; anything slower is better retried.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_ATTEMPTS: u32 = 3;
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+// A server asking us to wait longer than this is not worth holding a dictation
+// for; give up and let the caller fall back.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(20);
+
+/// Exponential backoff with full jitter, seeded from the clock so concurrent
+/// clients don't retry in lockstep.
+fn backoff_delay(attempt: u32) -> Duration {
+    let ceiling = BASE_BACKOFF
+        .saturating_mul(1 << attempt.min(16))
+        .min(MAX_BACKOFF);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    ceiling.mul_f64((nanos % 1000) as f64 / 1000.0)
+}
+
+pub struct CloudTranscription {
+    pub output: TranscriptionOutput,
+    pub bytes_uploaded: usize,
+}
+
+pub async fn transcribe(
+    provider: &ProviderConfig,
+    network: &NetworkSettings,
+    api_key: Option<&str>,
+    audio_data: &[f32],
+    sample_rate: u32,
+    language: Option<String>,
+    prompt: Option<String>,
+) -> Result<CloudTranscription, CloudError> {
+    if provider.requires_api_key() && api_key.is_none() {
+        return Err(CloudError::MissingApiKey(provider.name.clone()));
+    }
+
+    let samples = prepare_samples(audio_data, sample_rate).map_err(CloudError::Encoding)?;
+    let upload = Upload::encode(&samples, provider.upload_format).map_err(CloudError::Encoding)?;
+    let language = language.filter(|v| !v.trim().is_empty() && !v.eq_ignore_ascii_case("auto"));
+    let prompt = prompt.filter(|p| !p.trim().is_empty());
+
+    let client = net::client_builder(network)
+        .map_err(CloudError::Network)?
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| CloudError::Network(e.to_string()))?;
+
+    let max_bytes = provider.max_upload_bytes();
+    if upload.bytes.len() <= max_bytes {
+        let output = transcribe_upload(&client, provider, api_key, &upload, language, prompt).await?;
+        return Ok(CloudTranscription {
+            output,
+            bytes_uploaded: upload.bytes.len(),
+        });
+    }
+
+    // Size chunks by the compression ratio we just observed, with some headroom
+    // since quieter and louder stretches compress differently.
+    let max_chunk_len = (samples.len() as f64 * max_bytes as f64 / upload.bytes.len() as f64 * 0.9) as usize;
+    let ranges = pipeline::split_at_silence(&samples, max_chunk_len);
+    println!(
+        "✂️ Upload is {:.1} MB, splitting into {} chunks for {}",
+        upload.bytes.len() as f64 / (1024.0 * 1024.0),
+        ranges.len(),
+        provider.name
+    );
+
+    let requests = ranges.iter().enumerate().map(|(idx, range)| {
+        let client = &client;
+        let samples = &samples[range.clone()];
+        let language = language.clone();
+        // Carried-over context only precedes the first chunk.
+        let prompt = if idx == 0 { prompt.clone() } else { None };
+        async move {
+            let upload = Upload::encode(samples, provider.upload_format).map_err(CloudError::Encoding)?;
+            let output = transcribe_upload(client, provider, api_key, &upload, language, prompt).await?;
+            Ok::<_, CloudError>((output, upload.bytes.len()))
+        }
+    });
+    let results = futures_util::future::join_all(requests).await;
+
+    let mut stitched = TranscriptionOutput::default();
+    let mut texts = Vec::new();
+    let mut bytes_uploaded = 0;
+    for (range, result) in ranges.iter().zip(results) {
+        let (output, bytes) = result?;
+        bytes_uploaded += bytes;
+        let offset_ms = range.start as f64 * 1000.0 / UPLOAD_SAMPLE_RATE as f64;
+        texts.push(output.text);
+        stitched.segments.extend(output.segments.into_iter().map(|mut segment| {
+            segment.start_ms += offset_ms;
+            segment.end_ms += offset_ms;
+            segment
+        }));
+        if stitched.language.is_none() {
+            stitched.language = output.language;
+        }
+    }
+    stitched.text = texts
+        .into_iter()
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(CloudTranscription {
+        output: stitched,
+        bytes_uploaded,
+    })
+}
+
+/// Sends one upload, retrying transient failures.
+async fn transcribe_upload(
+    client: &reqwest::Client,
+    provider: &ProviderConfig,
+    api_key: Option<&str>,
+    upload: &Upload,
+    language: Option<String>,
+    prompt: Option<String>,
+) -> Result<TranscriptionOutput, CloudError> {
+    let mut attempt = 0;
+    loop {
+        let result = send_request(
+            client,
+            provider,
+            api_key,
+            upload,
+            language.clone(),
+            prompt.clone(),
+        )
+        .await;
+
+        let err = match result {
+            Ok(output) => return Ok(output),
+            Err(err) => err,
+        };
+
+        attempt += 1;
+        if attempt >= MAX_ATTEMPTS || !err.is_retryable() {
+            return Err(err);
+        }
+
+        let delay = match &err {
+            CloudError::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            } => {
+                if *retry_after > MAX_RETRY_AFTER {
+                    return Err(err);
+                }
+                *retry_after
+            }
+            _ => backoff_delay(attempt),
+        };
+        println!(
+            "🔁 {} attempt {}/{} failed ({}), retrying in {:?}",
+            provider.name, attempt, MAX_ATTEMPTS, err, delay
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn send_request(
+    client: &reqwest::Client,
+    provider: &ProviderConfig,
+    api_key: Option<&str>,
+    upload: &Upload,
+    language: Option<String>,
+    prompt: Option<String>,
+) -> Result<TranscriptionOutput, CloudError> {
+    let file_part = Part::bytes(upload.bytes.clone())
+        .file_name(upload.file_name)
+        .mime_str(upload.mime)
+        .map_err(CloudError::from_reqwest)?;
+
+    let mut form = Form::new()
+        .text("model", provider.model.clone())
+        .part("file", file_part)
+        .text("response_format", "verbose_json");
+
+    if let Some(lang) = language {
+        form = form.text("language", lang);
+    }
+
+    if let Some(prompt) = prompt {
+        form = form.text("prompt", prompt);
+    }
+
+    let mut request = client.post(provider.transcriptions_url()).multipart(form);
+    for (name, value) in &provider.extra_headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    request = match (&provider.auth, api_key) {
+        (AuthScheme::Bearer, Some(key)) => request.bearer_auth(key),
+        (AuthScheme::Header { name }, Some(key)) => request.header(name.as_str(), key),
+        (AuthScheme::None, _) => request,
+        (_, None) => return Err(CloudError::MissingApiKey(provider.name.clone())),
+    };
+
+    let res = request.send().await.map_err(CloudError::from_reqwest)?;
+
+    if !res.status().is_success() {
+        return Err(CloudError::from_response(res).await);
+    }
+
+    let body = res
+        .json::<TranscriptionResponse>()
+        .await
+        .map_err(|e| CloudError::InvalidResponse(e.to_string()))?;
+    Ok(body.into_output())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    struct MockRequest {
+        headers: HashMap<String, String>, // Names lowercased
+        body: Vec<u8>,
+    }
+
+    impl MockRequest {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers.get(name).map(String::as_str)
+        }
+
+        fn has_field(&self, name: &str) -> bool {
+            let needle = format!("name=\"{}\"", name);
+            self.body.windows(needle.len()).any(|w| w == needle.as_bytes())
+        }
+
+        /// Length in samples of the WAV file in the multipart body.
+        fn wav_samples(&self) -> u32 {
+            let start = self
+                .body
+                .windows(4)
+                .position(|w| w == b"RIFF")
+                .expect("upload has no WAV file");
+            hound::WavReader::new(Cursor::new(&self.body[start..]))
+                .expect("upload is not a valid WAV file")
+                .duration()
+        }
+    }
+
+    struct MockResponse {
+        status: u16,
+        headers: Vec<(&'static str, String)>,
+        body: String,
+    }
+
+    impl MockResponse {
+        fn ok(body: serde_json::Value) -> Self {
+            Self {
+                status: 200,
+                headers: Vec::new(),
+                body: body.to_string(),
+            }
+        }
+    }
+
+    type Responder = Arc<dyn Fn(&MockRequest) -> MockResponse + Send + Sync>;
+
+    /// Minimal HTTP/1.1 server that answers each request with the next queued
+    /// response, or `fallback` once the queue is empty, and records what it received.
+    struct MockServer {
+        base_url: String,
+        requests: Arc<Mutex<Vec<MockRequest>>>,
+    }
+
+    impl MockServer {
+        async fn start(queued: Vec<MockResponse>, fallback: Responder) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let queued = Arc::new(Mutex::new(VecDeque::from(queued)));
+
+            let recorded = requests.clone();
+            tokio::spawn(async move {
+                loop {
+                    let Ok((stream, _)) = listener.accept().await else {
+                        return;
+                    };
+                    let (recorded, queued, fallback) = (recorded.clone(), queued.clone(), fallback.clone());
+                    tokio::spawn(async move {
+                        let _ = handle(stream, recorded, queued, fallback).await;
+                    });
+                }
+            });
+
+            Self { base_url, requests }
+        }
+
+        fn requests(&self) -> std::sync::MutexGuard<'_, Vec<MockRequest>> {
+            self.requests.lock().unwrap()
+        }
+    }
+
+    async fn handle(
+        mut stream: TcpStream,
+        recorded: Arc<Mutex<Vec<MockRequest>>>,
+        queued: Arc<Mutex<VecDeque<MockResponse>>>,
+        fallback: Responder,
+    ) -> std::io::Result<()> {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 8192];
+        let header_end = loop {
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                return Ok(());
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        };
+
+        let headers: HashMap<String, String> = String::from_utf8_lossy(&buf[..header_end])
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect();
+        let content_length: usize = headers
+            .get("content-length")
+            .and_then(|v| v.parse().ok())
+            .expect("request has no Content-Length");
+        while buf.len() < header_end + content_length {
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        let request = MockRequest {
+            headers,
+            body: buf[header_end..].to_vec(),
+        };
+        let response = queued
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| fallback(&request));
+        recorded.lock().unwrap().push(request);
+
+        let mut head = format!(
+            "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            response.status,
+            response.body.len()
+        );
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(response.body.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    fn hello_response() -> Responder {
+        Arc::new(|_| {
+            MockResponse::ok(serde_json::json!({
+                "text": " Hello there. ",
+                "language": "english",
+                "segments": [{ "start": 0.0, "end": 1.0, "text": " Hello there." }],
+            }))
+        })
+    }
+
+    fn provider(base_url: &str, auth: AuthScheme) -> ProviderConfig {
+        ProviderConfig {
+            id: "mock".to_string(),
+            name: "Mock".to_string(),
+            base_url: base_url.to_string(),
+            model: "whisper-mock".to_string(),
+            extra_headers: HashMap::new(),
+            auth,
+            upload_format: UploadFormat::Flac,
+            max_upload_mb: None,
+            cost_per_hour_usd: None,
+        }
+    }
+
+    fn tone(secs: f32) -> Vec<f32> {
+        (0..(secs * UPLOAD_SAMPLE_RATE as f32) as usize)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / UPLOAD_SAMPLE_RATE as f32).sin() * 0.5)
+            .collect()
+    }
+
+    async fn run(provider: &ProviderConfig, api_key: Option<&str>, audio: &[f32]) -> Result<CloudTranscription, CloudError> {
+        transcribe(
+            provider,
+            &NetworkSettings::default(),
+            api_key,
+            audio,
+            UPLOAD_SAMPLE_RATE,
+            Some("en".to_string()),
+            None,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn bearer_auth_sends_authorization_header() {
+        let server = MockServer::start(Vec::new(), hello_response()).await;
+        let provider = provider(&server.base_url, AuthScheme::Bearer);
+
+        let result = run(&provider, Some("sk-test"), &tone(1.0)).await.unwrap();
+
+        assert_eq!(result.output.text, "Hello there.");
+        assert_eq!(result.output.language.as_deref(), Some("en"));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].header("authorization"), Some("Bearer sk-test"));
+        assert!(requests[0].header("content-type").unwrap().starts_with("multipart/form-data"));
+        for field in ["model", "file", "response_format", "language"] {
+            assert!(requests[0].has_field(field), "missing form field {}", field);
+        }
+        assert!(!requests[0].has_field("prompt"));
+    }
+
+    #[tokio::test]
+    async fn header_auth_sends_key_in_custom_header() {
+        let server = MockServer::start(Vec::new(), hello_response()).await;
+        let mut provider = provider(
+            &server.base_url,
+            AuthScheme::Header {
+                name: "X-Api-Key".to_string(),
+            },
+        );
+        provider.extra_headers.insert("X-Org".to_string(), "acme".to_string());
+
+        run(&provider, Some("secret"), &tone(1.0)).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("x-api-key"), Some("secret"));
+        assert_eq!(requests[0].header("x-org"), Some("acme"));
+        assert_eq!(requests[0].header("authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn no_auth_sends_no_credentials() {
+        let server = MockServer::start(Vec::new(), hello_response()).await;
+        let provider = provider(&server.base_url, AuthScheme::None);
+
+        run(&provider, None, &tone(1.0)).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].header("authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn missing_key_fails_without_a_request() {
+        let server = MockServer::start(Vec::new(), hello_response()).await;
+        let provider = provider(&server.base_url, AuthScheme::Bearer);
+
+        let result = run(&provider, None, &tone(1.0)).await;
+
+        assert!(matches!(result, Err(CloudError::MissingApiKey(_))));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rate_limit_is_retried_after_the_requested_delay() {
+        let rate_limited = MockResponse {
+            status: 429,
+            headers: vec![("Retry-After", "0".to_string())],
+            body: r#"{"error":{"message":"Slow down"}}"#.to_string(),
+        };
+        let server = MockServer::start(vec![rate_limited], hello_response()).await;
+        let provider = provider(&server.base_url, AuthScheme::Bearer);
+
+        let result = run(&provider, Some("sk-test"), &tone(1.0)).await.unwrap();
+
+        assert_eq!(result.output.text, "Hello there.");
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn split_upload_offsets_segments_by_chunk_start() {
+        // Each chunk is transcribed as one segment spanning the whole upload, so
+        // after stitching the segments should tile the recording end to end.
+        let whole_chunk: Responder = Arc::new(|request| {
+            let secs = request.wav_samples() as f64 / UPLOAD_SAMPLE_RATE as f64;
+            MockResponse::ok(serde_json::json!({
+                "text": "chunk",
+                "segments": [{ "start": 0.0, "end": secs, "text": "chunk" }],
+            }))
+        });
+        let server = MockServer::start(Vec::new(), whole_chunk).await;
+        let mut provider = provider(&server.base_url, AuthScheme::Bearer);
+        provider.upload_format = UploadFormat::Wav;
+        provider.max_upload_mb = Some(1);
+
+        // About 2.2 MB as 16-bit WAV, with a pause every five seconds to cut at.
+        let audio: Vec<f32> = tone(70.0)
+            .into_iter()
+            .enumerate()
+            .map(|(i, s)| if (i / UPLOAD_SAMPLE_RATE as usize) % 5 == 4 { 0.0 } else { s })
+            .collect();
+        let result = run(&provider, Some("sk-test"), &audio).await.unwrap();
+
+        let chunks = server.requests().len();
+        assert!(chunks >= 3, "expected at least 3 chunks, got {}", chunks);
+        let segments = &result.output.segments;
+        assert_eq!(segments.len(), chunks);
+        assert_eq!(segments[0].start_ms, 0.0);
+        for pair in segments.windows(2) {
+            assert!(pair[0].end_ms > pair[0].start_ms);
+            assert!((pair[1].start_ms - pair[0].end_ms).abs() < 1e-6);
+        }
+        let total_ms = audio.len() as f64 * 1000.0 / UPLOAD_SAMPLE_RATE as f64;
+        assert!((segments[chunks - 1].end_ms - total_ms).abs() < 1e-6);
+        assert_eq!(result.output.text, vec!["chunk"; chunks].join(" "));
+    }
+}
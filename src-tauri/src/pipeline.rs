@@ -0,0 +1,241 @@
+use crate::whisper::TranscriptionOutput;
+use crate::{dictionary, formatting, itn, postprocess, profiles, snippets, AppSettings};
+use std::ops::Range;
+use std::path::Path;
+use tauri::AppHandle;
+
+pub const SILENCE_THRESHOLD: f32 = 0.01;
+
+// Whisper's own thresholds for treating a decoded segment as silence.
+const NO_SPEECH_THRESHOLD: f32 = 0.6;
+const LOGPROB_THRESHOLD: f32 = -1.0;
+
+// Segments this unlikely are almost always hallucinations, whatever the backend.
+const MIN_AVG_LOGPROB: f32 = -2.0;
+
+// Roughly the size of a CoreAudio input callback, so offline clips are gated
+// the same way as live recordings.
+const LEVEL_WINDOW: usize = 512;
+
+pub fn has_meaningful_audio(audio_data: &[f32], peak_level: f32) -> bool {
+    !audio_data.is_empty() && peak_level >= SILENCE_THRESHOLD
+}
+
+/// Peak of the windowed RMS level, matching what `AudioManager` tracks while recording.
+pub fn peak_level(audio_data: &[f32]) -> f32 {
+    audio_data
+        .chunks(LEVEL_WINDOW)
+        .map(|window| (window.iter().map(|s| s * s).sum::<f32>() / window.len() as f32).sqrt())
+        .fold(0.0, f32::max)
+}
+
+/// Splits `audio_data` into pieces of at most `max_len` samples, cutting each
+/// one at the quietest window in its last third so words aren't chopped.
+pub fn split_at_silence(audio_data: &[f32], max_len: usize) -> Vec<Range<usize>> {
+    let max_len = max_len.max(LEVEL_WINDOW * 3);
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    while audio_data.len() - start > max_len {
+        let search_from = start + max_len * 2 / 3;
+        let search = &audio_data[search_from..start + max_len];
+        let quietest = search
+            .chunks(LEVEL_WINDOW)
+            .map(|window| window.iter().map(|s| s * s).sum::<f32>() / window.len() as f32)
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(idx, _)| idx)
+            .unwrap_or(0);
+        // Cut in the middle of the quiet window rather than at its edge.
+        let cut = (search_from + quietest * LEVEL_WINDOW + LEVEL_WINDOW / 2).min(start + max_len);
+        ranges.push(start..cut);
+        start = cut;
+    }
+
+    ranges.push(start..audio_data.len());
+    ranges
+}
+
+/// Cleans up backend output before it is inserted or stored. Returns `None` when
+/// nothing meaningful was transcribed.
+pub fn finalize_text(text: &str) -> Option<String> {
+    let trimmed = text.trim();
+    if trimmed.chars().all(|c| c.is_whitespace() || c.is_ascii_punctuation()) {
+        return None;
+    }
+    Some(trimmed.to_string())
+}
+
+/// Drops segments the decoder itself wasn't confident about, and rebuilds the
+/// text from what is left. Backends that don't report scores are unaffected.
+pub fn drop_low_confidence_segments(mut output: TranscriptionOutput) -> TranscriptionOutput {
+    let before = output.segments.len();
+    output.segments.retain(|segment| {
+        let logprob = segment.avg_logprob.unwrap_or(0.0);
+        let likely_silence = segment
+            .no_speech_prob
+            .map(|p| p > NO_SPEECH_THRESHOLD && logprob < LOGPROB_THRESHOLD)
+            .unwrap_or(false);
+        !likely_silence && logprob >= MIN_AVG_LOGPROB
+    });
+
+    let dropped = before - output.segments.len();
+    if dropped > 0 {
+        println!("🧹 Dropped {} low-confidence segment(s)", dropped);
+        output.text = output
+            .segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+    output
+}
+
+/// Confidence filtering and clean-up shared by dictation and eval runs.
+/// Returns `None` when nothing meaningful was transcribed.
+pub fn clean_output(output: TranscriptionOutput) -> Option<TranscriptionOutput> {
+    let mut output = drop_low_confidence_segments(output);
+    match finalize_text(&output.text) {
+        Some(text) => {
+            output.text = text;
+            Some(output)
+        }
+        None => {
+            println!("🔇 Skipping transcription - only contains punctuation/whitespace: '{}'", output.text.trim());
+            None
+        }
+    }
+}
+
+/// The settings and stores the text stages read, loaded up front so the stages
+/// themselves don't touch the app.
+pub struct TextStages {
+    pub settings: AppSettings,
+    pub dictionary: dictionary::DictionaryStore,
+    pub snippets: Vec<snippets::Snippet>,
+}
+
+impl TextStages {
+    pub fn load(app: &AppHandle) -> Self {
+        Self {
+            settings: AppSettings::get_or_default(app),
+            dictionary: dictionary::DictionaryStore::load(app).unwrap_or_default(),
+            snippets: snippets::SnippetStore::load(app).unwrap_or_default().snippets,
+        }
+    }
+
+    /// Loads from the app data directory without an app, for headless runs.
+    pub fn load_from_dir(data_dir: &Path) -> Result<Self, String> {
+        Ok(Self {
+            settings: AppSettings::load_from_dir(data_dir)?.unwrap_or_default(),
+            dictionary: dictionary::DictionaryStore::load_from(&data_dir.join("dictionary.json"))?,
+            snippets: snippets::SnippetStore::load_from(&data_dir.join("snippets.json"))?.snippets,
+        })
+    }
+
+    /// Turns cleaned-up backend output into the text that is inserted and stored.
+    /// `language` is the detected language, if the backend reported one. Hit
+    /// counts are only bumped in `self.dictionary`; the returned flag says
+    /// whether any rule fired, so the caller decides whether to save them.
+    pub fn apply(
+        &mut self,
+        raw_text: &str,
+        language: Option<&str>,
+        profile: Option<&profiles::Profile>,
+    ) -> (String, bool) {
+        let settings = &self.settings;
+        let language = language
+            .or(profile.and_then(|p| p.language.as_deref()))
+            .or(settings.whisper_language.as_deref())
+            .unwrap_or("en");
+        let (mut text, expansions) = snippets::mark(raw_text, &self.snippets);
+        if settings.formatting.enabled {
+            text = formatting::apply(&text, &formatting::commands_for(language, &settings.formatting));
+        }
+        let post_processing = profile
+            .and_then(|p| p.post_processing.as_ref())
+            .unwrap_or(&settings.post_processing);
+        let text = postprocess::process(&text, post_processing, language);
+        let text = itn::normalize(&text, language, &settings.itn);
+        let text = match profile {
+            Some(profile) => {
+                let vocabulary: Vec<String> = settings.vocabulary.iter().chain(&profile.vocabulary).cloned().collect();
+                profiles::apply_casing(&text, profile.casing, &vocabulary, language)
+            }
+            None => text,
+        };
+        let (text, fired) = dictionary::apply(&text, &mut self.dictionary.rules);
+        // Expansions go in verbatim, so they skip the clean-up stages.
+        (snippets::fill(&text, &expansions), fired)
+    }
+}
+
+/// Runs the text stages for a dictation and saves the replacement hit counts.
+pub fn process_text(
+    app: &AppHandle,
+    raw_text: &str,
+    language: Option<&str>,
+    profile: Option<&profiles::Profile>,
+) -> String {
+    let mut stages = TextStages::load(app);
+    let (text, fired) = stages.apply(raw_text, language, profile);
+    if fired {
+        if let Err(e) = stages.dictionary.save(app) {
+            eprintln!("Failed to save replacement hit counts: {}", e);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stages() -> TextStages {
+        TextStages {
+            settings: AppSettings::default(),
+            dictionary: dictionary::DictionaryStore::default(),
+            snippets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn repeated_digits_survive_stutter_collapse_for_itn() {
+        let (text, _) = stages().apply("Call five five five one two three four.", Some("en"), None);
+        assert_eq!(text, "Call 555-1234.");
+    }
+
+    #[test]
+    fn repeated_words_are_still_collapsed() {
+        let (text, _) = stages().apply("I I think the the plan works.", Some("en"), None);
+        assert_eq!(text, "I think the plan works.");
+    }
+
+    #[test]
+    fn command_words_in_running_speech_stay_words() {
+        let (text, _) = stages().apply("The trial period ends, so add a colon and a new line to the file.", Some("en"), None);
+        assert_eq!(text, "The trial period ends, so add a colon and a new line to the file.");
+    }
+
+    #[test]
+    fn commands_at_a_pause_are_applied() {
+        let (text, _) = stages().apply("Hello, comma, how are you, question mark. New paragraph. Thanks period", Some("en"), None);
+        assert_eq!(text, "Hello, how are you?\n\nThanks.");
+    }
+
+    #[test]
+    fn snippet_triggers_match_before_number_formatting() {
+        let mut stages = stages();
+        stages.snippets.push(snippets::Snippet {
+            id: "sig".to_string(),
+            trigger: "signature twenty".to_string(),
+            expansion: "Best,\nAda".to_string(),
+            created_at: 0.0,
+            updated_at: 0.0,
+        });
+        let (text, _) = stages.apply("Thanks for the twelve tips. Signature twenty.", Some("en"), None);
+        assert_eq!(text, "Thanks for the 12 tips. Best,\nAda");
+    }
+}
@@ -0,0 +1,271 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+// Words that really are doubled in ordinary speech ("I think that that works").
+const LEGITIMATE_REPEATS: &[&str] = &["that", "had", "is", "do"];
+
+fn english_filler_words() -> Vec<String> {
+    ["um", "umm", "uh", "uhh", "uhm", "erm", "er", "ah", "hmm", "mm", "you know"]
+        .iter()
+        .map(|w| w.to_string())
+        .collect()
+}
+
+// Only English has a default list: sounds like "er" are real words elsewhere
+// (German and Dutch "er"), so other languages keep every word until configured.
+fn default_filler_words() -> HashMap<String, Vec<String>> {
+    HashMap::from([("en".to_string(), english_filler_words())])
+}
+
+/// Older settings stored a single list, which was always the English one.
+fn deserialize_filler_words<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, Vec<String>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum FillerWords {
+        ByLanguage(HashMap<String, Vec<String>>),
+        English(Vec<String>),
+    }
+
+    Ok(match FillerWords::deserialize(deserializer)? {
+        FillerWords::ByLanguage(words) => words,
+        FillerWords::English(words) => HashMap::from([("en".to_string(), words)]),
+    })
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Which rule-based clean-up stages run on transcribed text, in pipeline order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct PostProcessSettings {
+    #[serde(default = "default_true")]
+    pub whitespace: bool,
+    #[serde(default = "default_true")]
+    pub fillers: bool,
+    #[serde(default = "default_true")]
+    pub stutters: bool,
+    #[serde(default = "default_true")]
+    pub capitalization: bool,
+    /// Keyed by language code.
+    #[serde(default = "default_filler_words", deserialize_with = "deserialize_filler_words")]
+    pub filler_words: HashMap<String, Vec<String>>,
+}
+
+impl Default for PostProcessSettings {
+    fn default() -> Self {
+        Self {
+            whitespace: true,
+            fillers: true,
+            stutters: true,
+            capitalization: true,
+            filler_words: default_filler_words(),
+        }
+    }
+}
+
+/// Runs every enabled stage over `text`, which is in `language`. Whitespace is
+/// normalized once more at the end since removing words leaves gaps behind.
+pub fn process(text: &str, settings: &PostProcessSettings, language: &str) -> String {
+    let mut text = text.to_string();
+    if settings.whitespace {
+        text = normalize_whitespace(&text);
+    }
+    if settings.fillers {
+        if let Some(fillers) = settings.filler_words.get(language) {
+            text = remove_fillers(&text, fillers);
+        }
+    }
+    if settings.stutters {
+        text = collapse_stutters(&text, language);
+    }
+    if settings.capitalization {
+        text = fix_capitalization(&text, language);
+    }
+    if settings.whitespace {
+        text = normalize_whitespace(&text);
+    }
+    // Line breaks at either end come from spoken formatting commands.
+    text.trim_matches(|c: char| c.is_whitespace() && c != '\n').to_string()
+}
+
+/// Collapses runs of spaces, drops spaces before punctuation and trims around
+/// line breaks, which are kept.
+pub fn normalize_whitespace(text: &str) -> String {
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            let collapsed = line.split_whitespace().collect::<Vec<_>>().join(" ");
+            let mut out = String::with_capacity(collapsed.len());
+            for c in collapsed.chars() {
+                if matches!(c, ',' | '.' | '!' | '?' | ';' | ':') && out.ends_with(' ') {
+                    out.pop();
+                }
+                out.push(c);
+            }
+            out
+        })
+        .collect();
+    lines.join("\n")
+}
+
+fn core_word(token: &str) -> String {
+    token
+        .trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+        .to_lowercase()
+}
+
+fn trailing_punctuation(token: &str) -> &str {
+    let end = token
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| !c.is_alphanumeric())
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(token.len());
+    &token[end..]
+}
+
+fn ends_sentence(punctuation: &str) -> bool {
+    punctuation.contains(['.', '!', '?'])
+}
+
+/// Removes filler words. Multi-word fillers like "you know" are only removed
+/// when set off by commas or sentence boundaries, so "do you know" survives.
+pub fn remove_fillers(text: &str, fillers: &[String]) -> String {
+    let fillers: Vec<Vec<String>> = fillers
+        .iter()
+        .map(|f| f.split_whitespace().map(|w| w.to_lowercase()).collect::<Vec<_>>())
+        .filter(|f| !f.is_empty())
+        .collect();
+
+    text.split('\n')
+        .map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let mut kept: Vec<String> = Vec::with_capacity(tokens.len());
+            let mut ended_with_filler = false;
+            let mut i = 0;
+
+            while i < tokens.len() {
+                let matched = fillers.iter().find(|filler| {
+                    if i + filler.len() > tokens.len() {
+                        return false;
+                    }
+                    let words_match = filler
+                        .iter()
+                        .zip(&tokens[i..])
+                        .all(|(f, t)| core_word(t) == *f);
+                    if !words_match || filler.len() == 1 {
+                        return words_match;
+                    }
+                    let before_ok = kept
+                        .last()
+                        .map(|prev| !trailing_punctuation(prev).is_empty())
+                        .unwrap_or(true);
+                    let after_ok = !trailing_punctuation(tokens[i + filler.len() - 1]).is_empty()
+                        || i + filler.len() == tokens.len();
+                    before_ok && after_ok
+                });
+
+                let Some(filler) = matched else {
+                    kept.push(tokens[i].to_string());
+                    ended_with_filler = false;
+                    i += 1;
+                    continue;
+                };
+
+                // Keep a sentence end the filler carried, e.g. "that's it, um."
+                let punctuation = trailing_punctuation(tokens[i + filler.len() - 1]);
+                if ends_sentence(punctuation) {
+                    if let Some(prev) = kept.last_mut() {
+                        let trimmed = prev.trim_end_matches([',', ';', ':']).to_string();
+                        *prev = format!("{}{}", trimmed, punctuation.trim_start_matches(','));
+                    }
+                }
+                i += filler.len();
+                ended_with_filler = true;
+            }
+
+            // "it was great, you know" shouldn't become "it was great,"
+            if ended_with_filler {
+                if let Some(last) = kept.last_mut() {
+                    *last = last.trim_end_matches([',', ';', ':']).to_string();
+                }
+            }
+            kept.join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Collapses "I I I think" to "I think" and drops cut-off starts like "th- the".
+/// Repeated number words are kept, since "five five five" is a real phone prefix.
+pub fn collapse_stutters(text: &str, language: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let mut kept: Vec<&str> = Vec::with_capacity(tokens.len());
+
+            for (i, token) in tokens.iter().enumerate() {
+                let next = tokens.get(i + 1).map(|t| core_word(t));
+
+                if token.ends_with('-') && token.len() > 1 {
+                    let fragment = core_word(token);
+                    if next.as_ref().is_some_and(|n| !fragment.is_empty() && n.starts_with(&fragment)) {
+                        continue;
+                    }
+                }
+
+                let core = core_word(token);
+                let repeated = next.as_ref() == Some(&core)
+                    && !core.is_empty()
+                    && trailing_punctuation(token).is_empty()
+                    && !LEGITIMATE_REPEATS.contains(&core.as_str())
+                    && !crate::itn::is_number_word(&core, language);
+                if repeated {
+                    continue;
+                }
+                kept.push(token);
+            }
+
+            kept.join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Capitalizes sentence starts and, in English, the pronoun "I" (including
+/// "i'm", "i've", ...). Elsewhere "i" is an ordinary word, e.g. the Italian article.
+pub fn fix_capitalization(text: &str, language: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut sentence_start = true;
+
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end();
+        let core = core_word(word);
+        let is_pronoun = language == "en" && (core == "i" || core.starts_with("i'"));
+
+        match word.char_indices().find(|(_, c)| c.is_alphabetic()) {
+            Some((idx, c)) if sentence_start || is_pronoun => {
+                out.push_str(&word[..idx]);
+                out.extend(c.to_uppercase());
+                out.push_str(&word[idx + c.len_utf8()..]);
+            }
+            _ => out.push_str(word),
+        }
+        out.push_str(&piece[word.len()..]);
+
+        // Bare symbols such as a "- " bullet don't end or start a sentence.
+        if word.chars().any(char::is_alphanumeric) {
+            sentence_start = ends_sentence(trailing_punctuation(word));
+        } else if ends_sentence(word) {
+            sentence_start = true;
+        }
+        if piece.contains('\n') {
+            sentence_start = true;
+        }
+    }
+    out
+}
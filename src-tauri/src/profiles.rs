@@ -0,0 +1,279 @@
+use crate::postprocess::PostProcessSettings;
+use crate::whisper::WhisperModelInfo;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct FocusedApp {
+    pub bundle_id: Option<String>, // e.g. "com.tinyspeck.slackmacgap"
+    pub name: String,
+}
+
+/// Reports which application has keyboard focus. Platforms without an
+/// implementation report nothing, which leaves dictation on the default
+/// settings unless a profile is picked by hand.
+pub trait FocusedAppProvider: Send + Sync {
+    fn focused_app(&self) -> Option<FocusedApp>;
+}
+
+#[cfg(not(target_os = "macos"))]
+pub struct NoFocusedApp;
+
+#[cfg(not(target_os = "macos"))]
+impl FocusedAppProvider for NoFocusedApp {
+    fn focused_app(&self) -> Option<FocusedApp> {
+        None
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub struct FrontmostApp;
+
+#[cfg(target_os = "macos")]
+impl FocusedAppProvider for FrontmostApp {
+    fn focused_app(&self) -> Option<FocusedApp> {
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::{NSAutoreleasePool, NSString};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        unsafe fn to_string(value: id) -> Option<String> {
+            if value == nil {
+                return None;
+            }
+            let bytes = value.UTF8String();
+            Some(std::ffi::CStr::from_ptr(bytes).to_string_lossy().into_owned())
+        }
+
+        unsafe {
+            let pool = NSAutoreleasePool::new(nil);
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let app: id = msg_send![workspace, frontmostApplication];
+            let focused = if app == nil {
+                None
+            } else {
+                let bundle_id = to_string(msg_send![app, bundleIdentifier]);
+                let name = to_string(msg_send![app, localizedName]);
+                match (name, bundle_id) {
+                    (Some(name), bundle_id) => Some(FocusedApp { bundle_id, name }),
+                    (None, Some(bundle_id)) => Some(FocusedApp {
+                        name: bundle_id.clone(),
+                        bundle_id: Some(bundle_id),
+                    }),
+                    (None, None) => None,
+                }
+            };
+            let _: () = msg_send![pool, release];
+            focused
+        }
+    }
+}
+
+pub fn default_provider() -> Box<dyn FocusedAppProvider> {
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(FrontmostApp)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Box::new(NoFocusedApp)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum CasingStyle {
+    /// Whatever the post-processing stages produced.
+    #[default]
+    Unchanged,
+    /// Capitalizes sentence starts even when the capitalization stage is off.
+    Sentence,
+    /// Casual chat style. Acronyms, "I" and vocabulary terms keep their casing.
+    Lowercase,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum InsertionMethod {
+    /// Pastes through the clipboard and restores its old contents afterwards.
+    #[default]
+    Paste,
+    /// Types the text as keystrokes, for apps that block or mangle pasting.
+    Type,
+    /// Only copies the text, leaving it to the user to paste.
+    Clipboard,
+}
+
+/// Settings that apply while dictating into particular apps. `None` fields
+/// fall back to the global settings.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Profile {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Bundle IDs ("com.microsoft.VSCode") or app names ("Slack"), matched
+    /// case-insensitively.
+    #[serde(default)]
+    pub apps: Vec<String>,
+    #[serde(default)]
+    pub backend_id: Option<String>,
+    /// Whisper model ID used when transcribing locally, including when a
+    /// cloud backend falls back to local Whisper.
+    #[serde(default)]
+    pub local_model: Option<String>,
+    /// Provider's model name used when transcribing with a cloud backend.
+    #[serde(default)]
+    pub cloud_model: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Used on top of the global vocabulary.
+    #[serde(default)]
+    pub vocabulary: Vec<String>,
+    #[serde(default)]
+    pub post_processing: Option<PostProcessSettings>,
+    #[serde(default)]
+    pub casing: CasingStyle,
+    #[serde(default)]
+    pub insertion: InsertionMethod,
+    /// Rewrite prompt applied to everything dictated with this profile.
+    #[serde(default)]
+    pub rewrite_prompt_id: Option<String>,
+}
+
+impl Profile {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Profile name must not be empty".to_string());
+        }
+        if let Some(id) = &self.local_model {
+            if WhisperModelInfo::get_by_id(id).is_none() {
+                return Err(format!("Unknown Whisper model: {}", id));
+            }
+        }
+        if self.cloud_model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            return Err("Cloud model must not be empty".to_string());
+        }
+        Ok(())
+    }
+
+    fn matches(&self, app: &FocusedApp) -> bool {
+        self.apps.iter().any(|pattern| {
+            let pattern = pattern.trim();
+            !pattern.is_empty()
+                && (pattern.eq_ignore_ascii_case(&app.name)
+                    || app
+                        .bundle_id
+                        .as_deref()
+                        .is_some_and(|id| pattern.eq_ignore_ascii_case(id)))
+        })
+    }
+}
+
+/// The profile an override names, or else the first profile listing `app`.
+pub fn resolve<'a>(
+    profiles: &'a [Profile],
+    override_id: Option<&str>,
+    app: Option<&FocusedApp>,
+) -> Option<&'a Profile> {
+    if let Some(id) = override_id {
+        if let Some(profile) = profiles.iter().find(|p| p.id == id) {
+            return Some(profile);
+        }
+    }
+    app.and_then(|app| profiles.iter().find(|p| p.matches(app)))
+}
+
+/// What a recording was started with, fixed when recording begins so that
+/// switching apps mid-dictation doesn't change how it's processed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct DictationContext {
+    pub profile: Option<Profile>,
+    pub source_app: Option<FocusedApp>,
+    pub hotkey_prompt_id: Option<String>, // Set when recording with a prompt's own hotkey
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type, tauri_specta::Event)]
+pub struct ProfileSelected {
+    pub profile_id: Option<String>,
+    pub profile_name: Option<String>,
+    pub source_app: Option<String>,
+}
+
+pub struct ProfileState {
+    provider: Box<dyn FocusedAppProvider>,
+    current: Mutex<DictationContext>,
+}
+
+impl ProfileState {
+    pub fn new(provider: Box<dyn FocusedAppProvider>) -> Self {
+        Self {
+            provider,
+            current: Mutex::new(DictationContext::default()),
+        }
+    }
+
+    pub fn focused_app(&self) -> Option<FocusedApp> {
+        self.provider.focused_app()
+    }
+
+    /// Picks the profile for `source_app` and remembers it for the recording
+    /// that's starting.
+    pub fn begin(
+        &self,
+        profiles: &[Profile],
+        override_id: Option<&str>,
+        source_app: Option<FocusedApp>,
+        hotkey_prompt_id: Option<String>,
+    ) -> DictationContext {
+        let profile = resolve(profiles, override_id, source_app.as_ref()).cloned();
+        match (&profile, &source_app) {
+            (Some(profile), Some(app)) => println!("🗂️ Using profile \"{}\" for {}", profile.name, app.name),
+            (Some(profile), None) => println!("🗂️ Using profile \"{}\"", profile.name),
+            (None, _) => {}
+        }
+        let context = DictationContext {
+            profile,
+            source_app,
+            hotkey_prompt_id,
+        };
+        *self.current.lock().unwrap() = context.clone();
+        context
+    }
+
+    pub fn current(&self) -> DictationContext {
+        self.current.lock().unwrap().clone()
+    }
+}
+
+fn keeps_case(word: &str, keep: &[String]) -> bool {
+    let core = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'');
+    core == "I"
+        || core.starts_with("I'")
+        || core.chars().skip(1).any(char::is_uppercase)
+        || keep.iter().any(|term| term.split_whitespace().any(|t| t == core))
+}
+
+/// Applies `style` to `text`, which is in `language`. `keep` lists terms whose
+/// spelling is left alone.
+pub fn apply_casing(text: &str, style: CasingStyle, keep: &[String], language: &str) -> String {
+    match style {
+        CasingStyle::Unchanged => text.to_string(),
+        CasingStyle::Sentence => crate::postprocess::fix_capitalization(text, language),
+        CasingStyle::Lowercase => {
+            let mut out = String::with_capacity(text.len());
+            let mut copied_to = 0;
+            for word in text.split_whitespace() {
+                let start = word.as_ptr() as usize - text.as_ptr() as usize;
+                out.push_str(&text[copied_to..start]);
+                if keeps_case(word, keep) {
+                    out.push_str(word);
+                } else {
+                    out.push_str(&word.to_lowercase());
+                }
+                copied_to = start + word.len();
+            }
+            out.push_str(&text[copied_to..]);
+            out
+        }
+    }
+}
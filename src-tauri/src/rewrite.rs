@@ -0,0 +1,288 @@
+use crate::net;
+use crate::openai_compat::AuthScheme;
+use crate::secrets::SecretStore;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+pub const API_KEY_SECRET: &str = "rewrite_api_key";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn default_timeout_secs() -> u32 {
+    20
+}
+
+fn default_prompts() -> Vec<RewritePrompt> {
+    [
+        (
+            "grammar",
+            "Fix grammar",
+            "Fix grammar, spelling and punctuation in the user's dictated text. Keep the wording and tone otherwise unchanged.",
+        ),
+        (
+            "email",
+            "Email",
+            "Rewrite the user's dictated text as a clear, friendly email body. Don't add a subject line or a signature.",
+        ),
+        (
+            "concise",
+            "Concise",
+            "Make the user's dictated text more concise without losing any information.",
+        ),
+    ]
+    .into_iter()
+    .map(|(id, name, system_prompt)| RewritePrompt {
+        id: id.to_string(),
+        name: name.to_string(),
+        system_prompt: system_prompt.to_string(),
+        hotkey: None,
+    })
+    .collect()
+}
+
+// Appended to every prompt so chatty models don't wrap the result in commentary.
+const OUTPUT_RULE: &str = "Reply with the resulting text only, without quotes, preamble or explanation.";
+
+/// Any server speaking the OpenAI `/chat/completions` API, such as a local
+/// llama.cpp server or a cloud provider.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RewriteEndpoint {
+    /// Base of the OpenAI-style API, e.g. `http://127.0.0.1:8080/v1`.
+    pub base_url: String,
+    pub model: String,
+    pub auth: AuthScheme,
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
+    /// Covers the whole response, streaming included.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u32,
+}
+
+impl Default for RewriteEndpoint {
+    fn default() -> Self {
+        Self {
+            base_url: "http://127.0.0.1:8080/v1".to_string(),
+            model: "local".to_string(), // llama.cpp serves whatever model it was started with
+            auth: AuthScheme::None,
+            extra_headers: HashMap::new(),
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+impl RewriteEndpoint {
+    pub fn chat_completions_url(&self) -> String {
+        format!("{}/chat/completions", self.base_url.trim_end_matches('/'))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.model.trim().is_empty() {
+            return Err("Rewrite model must not be empty".to_string());
+        }
+        let url = reqwest::Url::parse(&self.base_url)
+            .map_err(|e| format!("Invalid base URL '{}': {}", self.base_url, e))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(format!("Unsupported URL scheme: {}", url.scheme()));
+        }
+        if let AuthScheme::Header { name } = &self.auth {
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid auth header name: {}", name))?;
+        }
+        for (name, value) in &self.extra_headers {
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid header name: {}", name))?;
+            reqwest::header::HeaderValue::from_str(value)
+                .map_err(|_| format!("Invalid value for header {}", name))?;
+        }
+        if self.timeout_secs == 0 {
+            return Err("Rewrite timeout must be at least one second".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RewritePrompt {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub system_prompt: String,
+    /// Dictating with this hotkey held uses the prompt, whatever the profile says.
+    #[serde(default)]
+    pub hotkey: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RewriteSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub endpoint: RewriteEndpoint,
+    #[serde(default = "default_prompts")]
+    pub prompts: Vec<RewritePrompt>,
+    /// Used when neither the hotkey nor the profile picks a prompt. `None`
+    /// leaves such dictations alone.
+    #[serde(default)]
+    pub default_prompt_id: Option<String>,
+}
+
+impl Default for RewriteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: RewriteEndpoint::default(),
+            prompts: default_prompts(),
+            default_prompt_id: None,
+        }
+    }
+}
+
+impl RewriteSettings {
+    pub fn prompt(&self, id: &str) -> Option<&RewritePrompt> {
+        self.prompts.iter().find(|p| p.id == id)
+    }
+}
+
+pub fn get_api_key(app: &AppHandle) -> Option<String> {
+    app.state::<SecretStore>()
+        .get(API_KEY_SECRET)
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to read API key: {}", e);
+            None
+        })
+}
+
+pub fn has_api_key(app: &AppHandle) -> bool {
+    app.state::<SecretStore>().contains(API_KEY_SECRET)
+}
+
+pub fn set_api_key(app: &AppHandle, key: String) -> Result<(), String> {
+    app.state::<SecretStore>().set(API_KEY_SECRET, &key)
+}
+
+pub fn clear_api_key(app: &AppHandle) -> Result<(), String> {
+    app.state::<SecretStore>().delete(API_KEY_SECRET)
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage<'a>>,
+    stream: bool,
+    temperature: f32,
+}
+
+#[derive(Deserialize)]
+struct ChunkDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChunkChoice {
+    delta: ChunkDelta,
+}
+
+#[derive(Deserialize)]
+struct Chunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+}
+
+/// Sends `user` to the endpoint with `system` as the system prompt and
+/// streams the reply back, calling `on_partial` with the text so far.
+pub async fn complete(
+    app: &AppHandle,
+    endpoint: &RewriteEndpoint,
+    system: &str,
+    user: &str,
+    mut on_partial: impl FnMut(&str),
+) -> Result<String, String> {
+    let url = endpoint.chat_completions_url();
+    net::check_egress(app, "LLM rewrite", &url)?;
+
+    let api_key = get_api_key(app);
+    let client = net::client_builder(&crate::AppSettings::get_or_default(app).network)?
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let system = format!("{}\n\n{}", system.trim(), OUTPUT_RULE);
+    let body = ChatRequest {
+        model: &endpoint.model,
+        messages: vec![
+            ChatMessage { role: "system", content: &system },
+            ChatMessage { role: "user", content: user },
+        ],
+        stream: true,
+        temperature: 0.2,
+    };
+
+    let mut request = client.post(&url).json(&body);
+    for (name, value) in &endpoint.extra_headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    request = match (&endpoint.auth, api_key.as_deref()) {
+        (AuthScheme::Bearer, Some(key)) => request.bearer_auth(key),
+        (AuthScheme::Header { name }, Some(key)) => request.header(name.as_str(), key),
+        (AuthScheme::None, _) => request,
+        (_, None) => return Err("No API key set for the rewrite endpoint".to_string()),
+    };
+
+    let stream = async {
+        let res = request.send().await.map_err(|e| e.to_string())?;
+        let status = res.status();
+        if !status.is_success() {
+            let message = res.text().await.unwrap_or_default();
+            return Err(format!("HTTP {}: {}", status.as_u16(), message.trim()));
+        }
+
+        // Server-sent events: one `data: {...}` line per chunk, ending with `data: [DONE]`.
+        let mut bytes = res.bytes_stream();
+        let mut pending = Vec::new();
+        let mut text = String::new();
+        while let Some(chunk) = bytes.next().await {
+            pending.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+            while let Some(newline) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=newline).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim().strip_prefix("data:") else {
+                    continue;
+                };
+                let data = data.trim();
+                if data == "[DONE]" {
+                    return Ok(text);
+                }
+                let chunk: Chunk = serde_json::from_str(data)
+                    .map_err(|e| format!("Invalid response chunk: {}", e))?;
+                let delta: String = chunk
+                    .choices
+                    .into_iter()
+                    .filter_map(|c| c.delta.content)
+                    .collect();
+                if !delta.is_empty() {
+                    text.push_str(&delta);
+                    on_partial(&text);
+                }
+            }
+        }
+        Ok::<_, String>(text)
+    };
+
+    let text = tokio::time::timeout(Duration::from_secs(endpoint.timeout_secs as u64), stream)
+        .await
+        .map_err(|_| format!("No complete reply within {}s", endpoint.timeout_secs))??;
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("The model returned no text".to_string());
+    }
+    Ok(text.to_string())
+}
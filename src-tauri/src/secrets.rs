@@ -0,0 +1,445 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const KEYCHAIN_SERVICE: &str = "com.talktype.desktop";
+const INDEX_FILE: &str = "secrets-index.json";
+const ENCRYPTED_FILE: &str = "secrets.enc.json";
+
+/// Where secret values actually live. Implementations only ever see secret
+/// names and values; which names exist is tracked by `SecretStore`.
+pub trait SecretBackend: Send + Sync {
+    fn id(&self) -> &'static str;
+    fn get(&self, name: &str) -> Result<Option<String>, String>;
+    fn set(&self, name: &str, value: &str) -> Result<(), String>;
+    fn delete(&self, name: &str) -> Result<(), String>;
+
+    /// Which of `candidates` are stored. Backends that can list their
+    /// contents return everything they hold instead.
+    fn stored_names(&self, candidates: &[String]) -> Result<BTreeSet<String>, String> {
+        let mut names = BTreeSet::new();
+        for name in candidates {
+            if self.get(name)?.is_some() {
+                names.insert(name.clone());
+            }
+        }
+        Ok(names)
+    }
+}
+
+/// macOS Keychain, Windows Credential Manager or the Secret Service on Linux desktops.
+pub struct KeychainBackend;
+
+impl KeychainBackend {
+    const ID: &'static str = "keychain";
+
+    fn entry(name: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(KEYCHAIN_SERVICE, name).map_err(|e| format!("Keychain error: {}", e))
+    }
+
+    /// Headless Linux machines often have no Secret Service running, which only
+    /// shows up once we try to talk to it.
+    fn is_usable() -> bool {
+        match Self::entry("availability-probe") {
+            Ok(entry) => matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry)),
+            Err(_) => false,
+        }
+    }
+}
+
+impl SecretBackend for KeychainBackend {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, String> {
+        match Self::entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Failed to read {} from keychain: {}", name, e)),
+        }
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), String> {
+        Self::entry(name)?
+            .set_password(value)
+            .map_err(|e| format!("Failed to write {} to keychain: {}", name, e))
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        match Self::entry(name)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to delete {} from keychain: {}", name, e)),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct EncryptedFile {
+    salt: String, // Hex
+    entries: BTreeMap<String, EncryptedEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedEntry {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Fallback for machines without a keychain. Values are encrypted with a key
+/// derived from the machine ID and a per-install salt, so a copied file is
+/// useless elsewhere. It does not protect against someone who can already run
+/// code as this user on this machine.
+pub struct EncryptedFileBackend {
+    path: PathBuf,
+    file: Mutex<EncryptedFile>,
+    cipher: XChaCha20Poly1305,
+}
+
+impl EncryptedFileBackend {
+    const ID: &'static str = "encrypted_file";
+
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let file = if path.exists() {
+            let data = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read secrets file: {}", e))?;
+            serde_json::from_str(&data).map_err(|e| format!("Failed to parse secrets file: {}", e))?
+        } else {
+            // Any 24 random bytes will do as a salt.
+            let salt = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+            EncryptedFile {
+                salt: to_hex(&salt),
+                entries: BTreeMap::new(),
+            }
+        };
+
+        let salt = from_hex(&file.salt).ok_or_else(|| "Corrupt secrets file salt".to_string())?;
+        let cipher = XChaCha20Poly1305::new(&Self::derive_key(&salt));
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            cipher,
+        })
+    }
+
+    fn derive_key(salt: &[u8]) -> Key {
+        let machine_id = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+            .iter()
+            .find_map(|p| std::fs::read_to_string(p).ok())
+            .map(|id| id.trim().to_string())
+            .or_else(|| dirs::home_dir().map(|home| home.display().to_string()))
+            .unwrap_or_default();
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"talktype-secrets-v1");
+        hasher.update(machine_id.as_bytes());
+        hasher.update(salt);
+        Key::clone_from_slice(&hasher.finalize())
+    }
+
+    fn save(&self, file: &EncryptedFile) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(file)
+            .map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+        write_private(&self.path, &data)
+    }
+}
+
+impl SecretBackend for EncryptedFileBackend {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, String> {
+        let file = self.file.lock().unwrap();
+        let Some(entry) = file.entries.get(name) else {
+            return Ok(None);
+        };
+
+        let nonce = from_hex(&entry.nonce).ok_or_else(|| format!("Corrupt nonce for {}", name))?;
+        let ciphertext =
+            from_hex(&entry.ciphertext).ok_or_else(|| format!("Corrupt ciphertext for {}", name))?;
+        let plaintext = self
+            .cipher
+            .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| format!("Failed to decrypt {}; was the secrets file copied from another machine?", name))?;
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|e| format!("Invalid secret {}: {}", name, e))
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), String> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, value.as_bytes())
+            .map_err(|_| format!("Failed to encrypt {}", name))?;
+
+        let mut file = self.file.lock().unwrap();
+        file.entries.insert(
+            name.to_string(),
+            EncryptedEntry {
+                nonce: to_hex(&nonce),
+                ciphertext: to_hex(&ciphertext),
+            },
+        );
+        self.save(&file)
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        let mut file = self.file.lock().unwrap();
+        if file.entries.remove(name).is_some() {
+            self.save(&file)?;
+        }
+        Ok(())
+    }
+
+    fn stored_names(&self, _candidates: &[String]) -> Result<BTreeSet<String>, String> {
+        Ok(self.file.lock().unwrap().entries.keys().cloned().collect())
+    }
+}
+
+/// Keeps secrets for this session only. Used when the real store can't be
+/// opened, so the app still starts and keys entered now work until it quits.
+#[derive(Default)]
+pub struct MemoryBackend {
+    values: Mutex<BTreeMap<String, String>>,
+}
+
+impl SecretBackend for MemoryBackend {
+    fn id(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, String> {
+        Ok(self.values.lock().unwrap().get(name).cloned())
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), String> {
+        self.values.lock().unwrap().insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        self.values.lock().unwrap().remove(name);
+        Ok(())
+    }
+}
+
+/// Which backend was chosen and which secrets exist, kept next to (not inside)
+/// the secrets so presence checks never touch a value.
+#[derive(Default, Serialize, Deserialize)]
+struct SecretIndex {
+    backend: Option<String>,
+    names: BTreeSet<String>,
+}
+
+pub struct SecretStore {
+    backend: Box<dyn SecretBackend>,
+    index_path: Option<PathBuf>, // `None` when nothing is written to disk
+    index: Mutex<SecretIndex>,
+}
+
+impl SecretStore {
+    /// Opens the store, sticking with whichever backend was picked the first
+    /// time so existing secrets stay reachable. A missing index is rebuilt
+    /// from the backend; one that can't be read is an error rather than a
+    /// reason to start over and lose track of what's stored.
+    pub fn open(app: &AppHandle) -> Result<Self, String> {
+        let app_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+        std::fs::create_dir_all(&app_dir)
+            .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+        let index_path = app_dir.join(INDEX_FILE);
+        let encrypted_path = app_dir.join(ENCRYPTED_FILE);
+        let existing: Option<SecretIndex> = match std::fs::read_to_string(&index_path) {
+            Ok(data) => Some(
+                serde_json::from_str(&data).map_err(|e| format!("Failed to parse secrets index: {}", e))?,
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("Failed to read secrets index: {}", e)),
+        };
+
+        let backend_id = match existing.as_ref().and_then(|index| index.backend.as_deref()) {
+            Some(id) => id.to_string(),
+            // Without an index, an existing secrets file is the only sign of which backend was in use.
+            None if encrypted_path.exists() => EncryptedFileBackend::ID.to_string(),
+            None if KeychainBackend::is_usable() => KeychainBackend::ID.to_string(),
+            None => EncryptedFileBackend::ID.to_string(),
+        };
+        let backend: Box<dyn SecretBackend> = match backend_id.as_str() {
+            KeychainBackend::ID => Box::new(KeychainBackend),
+            _ => Box::new(EncryptedFileBackend::open(encrypted_path)?),
+        };
+        println!("🔐 Storing secrets in {}", backend.id());
+
+        let mut index = match existing {
+            Some(index) => index,
+            None => {
+                let names = backend.stored_names(&known_secret_names(app))?;
+                if !names.is_empty() {
+                    println!("🔐 Rebuilt secrets index with {} secret(s)", names.len());
+                }
+                SecretIndex { backend: None, names }
+            }
+        };
+        index.backend = Some(backend.id().to_string());
+        let store = Self {
+            backend,
+            index_path: Some(index_path),
+            index: Mutex::new(index),
+        };
+        store.save_index(&store.index.lock().unwrap())?;
+        Ok(store)
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            backend: Box::new(MemoryBackend::default()),
+            index_path: None,
+            index: Mutex::new(SecretIndex::default()),
+        }
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.index_path.is_some()
+    }
+
+    fn save_index(&self, index: &SecretIndex) -> Result<(), String> {
+        let Some(index_path) = &self.index_path else {
+            return Ok(());
+        };
+        let data = serde_json::to_string_pretty(index)
+            .map_err(|e| format!("Failed to serialize secrets index: {}", e))?;
+        write_private(index_path, &data)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index.lock().unwrap().names.contains(name)
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<String>, String> {
+        if !self.contains(name) {
+            return Ok(None);
+        }
+        self.backend.get(name)
+    }
+
+    pub fn set(&self, name: &str, value: &str) -> Result<(), String> {
+        self.backend.set(name, value)?;
+        let mut index = self.index.lock().unwrap();
+        if index.names.insert(name.to_string()) {
+            self.save_index(&index)?;
+        }
+        Ok(())
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        self.backend.delete(name)?;
+        let mut index = self.index.lock().unwrap();
+        if index.names.remove(name) {
+            self.save_index(&index)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every secret name the app uses, for backends that can't list what they hold.
+fn known_secret_names(app: &AppHandle) -> Vec<String> {
+    let mut names = crate::openai_compat::api_key_secret_names(app);
+    names.push(crate::rewrite::API_KEY_SECRET.to_string());
+    names
+}
+
+/// API keys used to be stored in plaintext in settings.json. Move any that are
+/// still there into the secret store and remove them from the settings file.
+pub fn migrate_plaintext_secrets(app: &AppHandle) {
+    use tauri_plugin_store::StoreExt;
+
+    let Ok(store) = app.store("settings.json") else {
+        return;
+    };
+    let secrets = app.state::<SecretStore>();
+    // Moving keys into a store that forgets them on quit would lose them.
+    if !secrets.is_persistent() {
+        return;
+    }
+
+    let names: Vec<String> = store
+        .keys()
+        .into_iter()
+        .filter(|k| k == crate::openai_compat::GROQ_STORE_KEY || k.starts_with("provider_api_key:"))
+        .collect();
+
+    let mut migrated = 0;
+    for name in names {
+        let Some(value) = store.get(&name).and_then(|v| v.as_str().map(|s| s.to_string())) else {
+            continue;
+        };
+        match secrets.set(&name, &value) {
+            Ok(()) => {
+                store.delete(&name);
+                migrated += 1;
+            }
+            Err(e) => eprintln!("Failed to migrate {}: {}", name, e),
+        }
+    }
+
+    if migrated > 0 {
+        println!("🔐 Moved {} API key(s) out of settings.json", migrated);
+        let _ = store.save();
+    }
+}
+
+/// Writes `data` to a temp file that only this user can read, then renames it
+/// over `path`, so the file is never briefly world-readable or half-written.
+fn write_private(path: &Path, data: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    let tmp_path = path.with_extension("tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let write = || -> std::io::Result<()> {
+        let mut file = options.open(&tmp_path)?;
+        // `mode` only applies when the file is created, not to a leftover one.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(data.as_bytes())?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)
+    };
+    write().map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        format!("Failed to write {}: {}", path.display(), e)
+    })
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
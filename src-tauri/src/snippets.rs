@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Snippet {
+    pub id: String,
+    pub trigger: String,
+    /// May contain `{date}`, `{time}` and `{clipboard}`, filled in when the snippet fires.
+    pub expansion: String,
+    pub created_at: f64, // Unix timestamp in milliseconds
+    pub updated_at: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
+pub struct SnippetStore {
+    pub snippets: Vec<Snippet>,
+}
+
+fn core_word(token: &str) -> String {
+    token
+        .trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+        .to_lowercase()
+}
+
+fn trigger_words(trigger: &str) -> Vec<String> {
+    trigger
+        .split_whitespace()
+        .map(core_word)
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+impl SnippetStore {
+    pub fn get_store_path(app: &AppHandle) -> Result<PathBuf, String> {
+        let app_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+        if !app_dir.exists() {
+            std::fs::create_dir_all(&app_dir)
+                .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+        }
+
+        Ok(app_dir.join("snippets.json"))
+    }
+
+    pub fn load(app: &AppHandle) -> Result<Self, String> {
+        Self::load_from(&Self::get_store_path(app)?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read snippets file: {}", e))?;
+
+        serde_json::from_str(&data).map_err(|e| format!("Failed to parse snippets: {}", e))
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let path = Self::get_store_path(app)?;
+
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize snippets: {}", e))?;
+
+        std::fs::write(&path, data).map_err(|e| format!("Failed to write snippets file: {}", e))
+    }
+
+    fn validate(&self, id: Option<&str>, trigger: &str, expansion: &str) -> Result<(), String> {
+        let words = trigger_words(trigger);
+        if words.is_empty() {
+            return Err("Snippet trigger can't be empty".to_string());
+        }
+        if expansion.is_empty() {
+            return Err("Snippet expansion can't be empty".to_string());
+        }
+        let duplicate = self
+            .snippets
+            .iter()
+            .any(|s| Some(s.id.as_str()) != id && trigger_words(&s.trigger) == words);
+        if duplicate {
+            return Err(format!("A snippet for \"{}\" already exists", trigger.trim()));
+        }
+        Ok(())
+    }
+
+    pub fn add_snippet(&mut self, trigger: String, expansion: String) -> Result<Snippet, String> {
+        self.validate(None, &trigger, &expansion)?;
+        let now = chrono::Utc::now().timestamp_millis() as f64;
+        let snippet = Snippet {
+            id: uuid::Uuid::new_v4().to_string(),
+            trigger: trigger.trim().to_string(),
+            expansion,
+            created_at: now,
+            updated_at: now,
+        };
+        self.snippets.push(snippet.clone());
+        Ok(snippet)
+    }
+
+    pub fn update_snippet(&mut self, id: &str, trigger: String, expansion: String) -> Result<Snippet, String> {
+        self.validate(Some(id), &trigger, &expansion)?;
+        let snippet = self
+            .snippets
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or_else(|| "Snippet not found".to_string())?;
+        snippet.trigger = trigger.trim().to_string();
+        snippet.expansion = expansion;
+        snippet.updated_at = chrono::Utc::now().timestamp_millis() as f64;
+        Ok(snippet.clone())
+    }
+
+    pub fn delete_snippet(&mut self, id: &str) -> Result<(), String> {
+        let initial_len = self.snippets.len();
+        self.snippets.retain(|s| s.id != id);
+
+        if self.snippets.len() == initial_len {
+            Err("Snippet not found".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn fill_variables(expansion: &str) -> String {
+    let now = chrono::Local::now();
+    let mut text = expansion
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H:%M").to_string());
+    if text.contains("{clipboard}") {
+        text = text.replace("{clipboard}", &read_clipboard().unwrap_or_default());
+    }
+    text
+}
+
+#[cfg(target_os = "macos")]
+fn read_clipboard() -> Option<String> {
+    use cocoa::appkit::NSPasteboardTypeString;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let pool = NSAutoreleasePool::new(nil);
+        let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+        let contents: id = msg_send![pasteboard, stringForType: NSPasteboardTypeString];
+        let text = if contents == nil {
+            None
+        } else {
+            let bytes = contents.UTF8String();
+            Some(std::ffi::CStr::from_ptr(bytes).to_string_lossy().into_owned())
+        };
+        let _: () = msg_send![pool, release];
+        text
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn read_clipboard() -> Option<String> {
+    None
+}
+
+// Stand-ins for expansions while the text stages run, from a Unicode
+// private use plane so they can't clash with dictated text.
+const PLACEHOLDER_BASE: u32 = 0xF0000;
+
+/// Swaps every trigger phrase in `text` for a placeholder character and
+/// returns the expansions in placeholder order. Triggers match whole words,
+/// ignoring case and punctuation, and the longest trigger wins where two
+/// overlap. Text between matches is kept byte for byte.
+///
+/// Matching happens on the transcript as Whisper wrote it, so triggers with
+/// number or command words ("signature two") still match; `fill` puts the
+/// expansions back once the other stages are done with the text.
+pub fn mark(text: &str, snippets: &[Snippet]) -> (String, Vec<String>) {
+    let mut triggers: Vec<(Vec<String>, &Snippet)> = snippets
+        .iter()
+        .map(|s| (trigger_words(&s.trigger), s))
+        .filter(|(words, _)| !words.is_empty())
+        .collect();
+    if triggers.is_empty() {
+        return (text.to_string(), Vec::new());
+    }
+    triggers.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+
+    let spans: Vec<(usize, usize)> = text
+        .split_whitespace()
+        .map(|word| {
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            (start, start + word.len())
+        })
+        .collect();
+    let cores: Vec<String> = spans.iter().map(|&(s, e)| core_word(&text[s..e])).collect();
+
+    let mut out = String::with_capacity(text.len());
+    let mut expansions = Vec::new();
+    let mut copied_to = 0;
+    let mut i = 0;
+    while i < spans.len() {
+        let matched = triggers.iter().find(|(words, _)| {
+            i + words.len() <= cores.len() && words.iter().zip(&cores[i..]).all(|(w, c)| w == c)
+        });
+        let placeholder = char::from_u32(PLACEHOLDER_BASE + expansions.len() as u32);
+        let (Some((words, snippet)), Some(placeholder)) = (matched, placeholder) else {
+            i += 1;
+            continue;
+        };
+
+        println!("🧩 Expanding snippet \"{}\"", snippet.trigger);
+        out.push_str(&text[copied_to..spans[i].0]);
+        out.push(placeholder);
+        expansions.push(fill_variables(&snippet.expansion));
+        copied_to = spans[i + words.len() - 1].1;
+        i += words.len();
+    }
+    out.push_str(&text[copied_to..]);
+    (out, expansions)
+}
+
+/// Replaces the placeholders left by `mark` with their expansions, verbatim.
+pub fn fill(text: &str, expansions: &[String]) -> String {
+    if expansions.is_empty() {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match (c as u32).checked_sub(PLACEHOLDER_BASE).and_then(|i| expansions.get(i as usize)) {
+            Some(expansion) => out.push_str(expansion),
+            None => out.push(c),
+        }
+    }
+    out
+}
@@ -1,4 +1,6 @@
+use crate::profiles::FocusedApp;
 use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
 
@@ -11,6 +13,91 @@ pub struct Transcript {
     pub word_count: u32,
     pub wpm: f32,
     pub model_used: Option<String>,
+    #[serde(default)]
+    pub draft_text: Option<String>, // Set when a two-pass refinement replaced the draft
+    #[serde(default)]
+    pub segments: Vec<TranscriptSegment>,
+    #[serde(default)]
+    pub language: Option<String>, // ISO 639-1 code detected or forced during decoding
+    #[serde(default)]
+    pub raw_text: Option<String>, // Backend output before post-processing
+    #[serde(default)]
+    pub original_text: Option<String>, // Set when the user edited the text
+    #[serde(default)]
+    pub edited_at: Option<f64>,
+    #[serde(default)]
+    pub profile_id: Option<String>, // Dictation profile in effect when recording started
+    #[serde(default)]
+    pub source_app: Option<FocusedApp>,
+    #[serde(default)]
+    pub pre_rewrite_text: Option<String>, // Set when the LLM rewrite stage changed the text
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct TranscriptSegment {
+    pub start_ms: f64,
+    pub end_ms: f64,
+    pub text: String,
+    pub speaker: Option<u32>, // 1-based, only set by diarization models
+    pub speaker_turn_next: bool,
+    #[serde(default)]
+    pub avg_logprob: Option<f32>,
+    #[serde(default)]
+    pub no_speech_prob: Option<f32>, // Only reported by cloud providers
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, specta::Type)]
+pub enum ExportFormat {
+    PlainText,
+    Markdown,
+}
+
+impl Transcript {
+    /// Consecutive segments from the same speaker, merged into one block of text.
+    pub fn speaker_blocks(&self) -> Vec<(u32, String)> {
+        let mut blocks: Vec<(u32, String)> = Vec::new();
+        for segment in &self.segments {
+            let Some(speaker) = segment.speaker else {
+                continue;
+            };
+            match blocks.last_mut() {
+                Some((last, text)) if *last == speaker => {
+                    text.push(' ');
+                    text.push_str(&segment.text);
+                }
+                _ => blocks.push((speaker, segment.text.clone())),
+            }
+        }
+        blocks
+    }
+
+    pub fn render(&self, format: ExportFormat) -> String {
+        let mut out = String::new();
+        let when = chrono::DateTime::from_timestamp_millis(self.timestamp as i64)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+
+        if let ExportFormat::Markdown = format {
+            let _ = writeln!(out, "## {}\n", when);
+        }
+
+        let blocks = self.speaker_blocks();
+        if blocks.is_empty() {
+            let _ = writeln!(out, "{}", self.text);
+        } else {
+            for (speaker, text) in blocks {
+                match format {
+                    ExportFormat::PlainText => {
+                        let _ = writeln!(out, "Speaker {}:\n{}\n", speaker, text);
+                    }
+                    ExportFormat::Markdown => {
+                        let _ = writeln!(out, "**Speaker {}:** {}\n", speaker, text);
+                    }
+                }
+            }
+        }
+        out.trim_end().to_string()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
@@ -75,10 +162,72 @@ impl TranscriptStore {
         }
     }
 
+    /// Tail of the most recent transcript if it was saved after `since_ms`, trimmed
+    /// to at most `max_chars` and cut at a word boundary.
+    pub fn recent_context(&self, since_ms: f64, max_chars: usize) -> Option<String> {
+        let latest = self.transcripts.first().filter(|t| t.timestamp >= since_ms)?;
+        let text = latest.text.trim();
+        let char_count = text.chars().count();
+        if max_chars == 0 || char_count == 0 {
+            return None;
+        }
+        if char_count <= max_chars {
+            return Some(text.to_string());
+        }
+        
+        let tail: String = text.chars().skip(char_count - max_chars).collect();
+        let tail = match tail.find(char::is_whitespace) {
+            Some(idx) => tail[idx..].trim_start().to_string(),
+            None => tail,
+        };
+        Some(tail)
+    }
+
     pub fn get_transcript_by_id(&self, id: &str) -> Option<&Transcript> {
         self.transcripts.iter().find(|t| t.id == id)
     }
 
+    /// Replaces a draft transcript with the accurate second-pass text, keeping the draft around.
+    pub fn apply_refinement(
+        &mut self,
+        id: &str,
+        raw_text: String,
+        text: String,
+        segments: Vec<TranscriptSegment>,
+    ) -> Result<(), String> {
+        let transcript = self
+            .transcripts
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or_else(|| "Transcript not found".to_string())?;
+        if transcript.edited_at.is_some() {
+            return Err("Transcript was edited before refinement finished".to_string());
+        }
+        
+        let draft = std::mem::replace(&mut transcript.text, text);
+        transcript.draft_text.get_or_insert(draft);
+        transcript.raw_text = Some(raw_text);
+        transcript.segments = segments;
+        transcript.word_count = transcript.text.split_whitespace().count() as u32;
+        Ok(())
+    }
+
+    /// Replaces the text with a user edit. The first edit keeps the transcribed
+    /// text in `original_text`; returns the text that was replaced.
+    pub fn update_text(&mut self, id: &str, text: String) -> Result<String, String> {
+        let transcript = self
+            .transcripts
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or_else(|| "Transcript not found".to_string())?;
+        
+        let previous = std::mem::replace(&mut transcript.text, text);
+        transcript.original_text.get_or_insert_with(|| previous.clone());
+        transcript.edited_at = Some(chrono::Utc::now().timestamp_millis() as f64);
+        transcript.word_count = transcript.text.split_whitespace().count() as u32;
+        Ok(previous)
+    }
+
     pub fn delete_transcript(&mut self, id: &str) -> Result<(), String> {
         let initial_len = self.transcripts.len();
         self.transcripts.retain(|t| t.id != id);
@@ -90,6 +239,15 @@ impl TranscriptStore {
         }
     }
 
+    pub fn export(&self, ids: Option<&[String]>, format: ExportFormat) -> String {
+        self.transcripts
+            .iter()
+            .filter(|t| ids.map_or(true, |ids| ids.contains(&t.id)))
+            .map(|t| t.render(format))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
     pub fn clear_all(&mut self) {
         self.transcripts.clear();
     }
@@ -9,6 +9,14 @@ use crate::permissions::{Permissions, PermissionState};
 use crate::AppSettings;
 use std::sync::Arc;
 
+fn tray_tooltip(privacy_mode: bool) -> &'static str {
+    if privacy_mode {
+        "TalkType (Privacy Mode: offline)"
+    } else {
+        "TalkType"
+    }
+}
+
 pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
     let devices = std::thread::spawn(|| {
         tauri::async_runtime::block_on(async {
@@ -29,6 +37,14 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
         }).join().unwrap_or(None)
     };
     
+    let privacy_status = if settings.privacy_mode {
+        Some(MenuItem::with_id(app, "privacy_status", "🔒 Privacy Mode: network disabled", false, None::<&str>)?)
+    } else {
+        None
+    };
+    let privacy_label = if settings.privacy_mode { "Disable Privacy Mode" } else { "Enable Privacy Mode" };
+    let toggle_privacy = MenuItem::with_id(app, "toggle_privacy_mode", privacy_label, true, None::<&str>)?;
+    
     let open_main_window = MenuItem::with_id(app, "open_main_window", "Open Main Window", true, None::<&str>)?;
     
     let mut mic_items = vec![];
@@ -78,7 +94,13 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
     };
     let sep2 = PredefinedMenuItem::separator(app)?;
     
-    let mut menu_items: Vec<&dyn tauri::menu::IsMenuItem<R>> = vec![&open_main_window, &select_microphone];
+    let mut menu_items: Vec<&dyn tauri::menu::IsMenuItem<R>> = Vec::new();
+    if let Some(ref item) = privacy_status {
+        menu_items.push(item);
+    }
+    menu_items.push(&open_main_window);
+    menu_items.push(&select_microphone);
+    menu_items.push(&toggle_privacy);
     
     if let Some(ref sep) = sep1 {
         menu_items.push(sep);
@@ -117,6 +139,7 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
     let _ = TrayIconBuilder::with_id("main")
         .icon(tray_icon.unwrap_or_else(|| app.default_window_icon().unwrap().clone()))
         .icon_as_template(false)
+        .tooltip(tray_tooltip(settings.privacy_mode))
         .menu(&menu)
         .show_menu_on_left_click(true)
         .on_menu_event(move |app: &AppHandle<R>, event| {
@@ -142,6 +165,12 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
                             .spawn();
                     }
                 }
+                "toggle_privacy_mode" => {
+                    let mut settings = AppSettings::get_or_default(app);
+                    settings.privacy_mode = !settings.privacy_mode;
+                    let _ = AppSettings::set(app, &settings);
+                    let _ = update_tray_menu(app);
+                }
                 "quit" => {
                     app.exit(0);
                 }
@@ -190,6 +219,14 @@ pub fn update_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
             }).join().unwrap_or(None)
         };
         
+        let privacy_status = if settings.privacy_mode {
+            Some(MenuItem::with_id(app, "privacy_status", "🔒 Privacy Mode: network disabled", false, None::<&str>)?)
+        } else {
+            None
+        };
+        let privacy_label = if settings.privacy_mode { "Disable Privacy Mode" } else { "Enable Privacy Mode" };
+        let toggle_privacy = MenuItem::with_id(app, "toggle_privacy_mode", privacy_label, true, None::<&str>)?;
+        
         let open_main_window = MenuItem::with_id(app, "open_main_window", "Open Main Window", true, None::<&str>)?;
         
         let mut mic_items = vec![];
@@ -239,7 +276,13 @@ pub fn update_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
         };
         let sep2 = PredefinedMenuItem::separator(app)?;
         
-        let mut menu_items: Vec<&dyn tauri::menu::IsMenuItem<R>> = vec![&open_main_window, &select_microphone];
+        let mut menu_items: Vec<&dyn tauri::menu::IsMenuItem<R>> = Vec::new();
+        if let Some(ref item) = privacy_status {
+            menu_items.push(item);
+        }
+        menu_items.push(&open_main_window);
+        menu_items.push(&select_microphone);
+        menu_items.push(&toggle_privacy);
         
         if let Some(ref sep) = sep1 {
             menu_items.push(sep);
@@ -258,6 +301,7 @@ pub fn update_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
         
         let menu = Menu::with_items(app, &menu_items)?;
         tray.set_menu(Some(menu))?;
+        tray.set_tooltip(Some(tray_tooltip(settings.privacy_mode)))?;
     }
     
     Ok(())
@@ -0,0 +1,254 @@
+use chrono::{Datelike, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+// A bit over a year, so this month can be compared with the same month last year.
+const RETENTION_DAYS: i64 = 400;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct UsageRecord {
+    pub timestamp: f64, // Unix timestamp in milliseconds
+    pub provider_id: String,
+    pub model: String,
+    pub audio_secs: f64,
+    pub bytes_uploaded: f64,
+    pub latency_ms: f64,
+    pub estimated_cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct UsageTotals {
+    pub period: String, // "2026-10-18" for days, "2026-10" for months
+    pub requests: u32,
+    pub audio_secs: f64,
+    pub bytes_uploaded: f64,
+    pub estimated_cost_usd: f64,
+    pub by_provider: BTreeMap<String, f64>, // Audio seconds per provider ID
+}
+
+impl UsageTotals {
+    fn add(&mut self, record: &UsageRecord) {
+        self.requests += 1;
+        self.audio_secs += record.audio_secs;
+        self.bytes_uploaded += record.bytes_uploaded;
+        self.estimated_cost_usd += record.estimated_cost_usd.unwrap_or(0.0);
+        *self.by_provider.entry(record.provider_id.clone()).or_default() += record.audio_secs;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct UsageSummary {
+    pub today: UsageTotals,
+    pub this_month: UsageTotals,
+    pub daily: Vec<UsageTotals>,   // Newest first
+    pub monthly: Vec<UsageTotals>, // Newest first
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct LimitThresholds {
+    pub daily_minutes: Option<f64>,
+    pub monthly_minutes: Option<f64>,
+    pub monthly_cost_usd: Option<f64>,
+}
+
+/// Soft limits only warn; hard limits stop cloud requests so dictation falls
+/// back to local Whisper until the period rolls over.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct UsageLimits {
+    pub soft: LimitThresholds,
+    pub hard: LimitThresholds,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageLog {
+    pub records: Vec<UsageRecord>,
+    // Soft-limit warnings already shown, e.g. "daily_minutes:2026-10-18",
+    // so each one fires once per period.
+    #[serde(default)]
+    warned: BTreeSet<String>,
+}
+
+fn day_key(timestamp_ms: f64) -> String {
+    Local
+        .timestamp_millis_opt(timestamp_ms as i64)
+        .single()
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+fn month_key(timestamp_ms: f64) -> String {
+    Local
+        .timestamp_millis_opt(timestamp_ms as i64)
+        .single()
+        .map(|t| format!("{:04}-{:02}", t.year(), t.month()))
+        .unwrap_or_default()
+}
+
+impl UsageLog {
+    pub fn get_store_path(app: &AppHandle) -> Result<PathBuf, String> {
+        let app_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+        if !app_dir.exists() {
+            std::fs::create_dir_all(&app_dir)
+                .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+        }
+
+        Ok(app_dir.join("usage.json"))
+    }
+
+    pub fn load(app: &AppHandle) -> Result<Self, String> {
+        let path = Self::get_store_path(app)?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let data = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read usage file: {}", e))?;
+
+        serde_json::from_str(&data).map_err(|e| format!("Failed to parse usage: {}", e))
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let path = Self::get_store_path(app)?;
+
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize usage: {}", e))?;
+
+        std::fs::write(&path, data).map_err(|e| format!("Failed to write usage file: {}", e))
+    }
+
+    pub fn add_record(&mut self, record: UsageRecord) {
+        let cutoff = record.timestamp - (RETENTION_DAYS * 24 * 60 * 60 * 1000) as f64;
+        self.records.retain(|r| r.timestamp >= cutoff);
+
+        // Warnings for periods that are over can't fire again.
+        let (day, month) = (day_key(record.timestamp), month_key(record.timestamp));
+        self.warned
+            .retain(|w| w.split_once(':').is_some_and(|(_, period)| period == day || period == month));
+
+        self.records.push(record);
+    }
+
+    fn totals_by(&self, key: fn(f64) -> String) -> Vec<UsageTotals> {
+        let mut totals: BTreeMap<String, UsageTotals> = BTreeMap::new();
+        for record in &self.records {
+            let period = key(record.timestamp);
+            totals
+                .entry(period.clone())
+                .or_insert_with(|| UsageTotals {
+                    period,
+                    ..Default::default()
+                })
+                .add(record);
+        }
+        totals.into_values().rev().collect()
+    }
+
+    pub fn summary(&self) -> UsageSummary {
+        let now = chrono::Utc::now().timestamp_millis() as f64;
+        let daily = self.totals_by(day_key);
+        let monthly = self.totals_by(month_key);
+
+        let find = |totals: &[UsageTotals], period: String| {
+            totals
+                .iter()
+                .find(|t| t.period == period)
+                .cloned()
+                .unwrap_or(UsageTotals {
+                    period,
+                    ..Default::default()
+                })
+        };
+
+        UsageSummary {
+            today: find(&daily, day_key(now)),
+            this_month: find(&monthly, month_key(now)),
+            daily,
+            monthly,
+        }
+    }
+
+    /// Names of the thresholds in `limits` that current usage has reached, with
+    /// the period they apply to.
+    fn exceeded(&self, limits: &LimitThresholds) -> Vec<(String, &'static str)> {
+        let summary = self.summary();
+        let mut exceeded = Vec::new();
+        if limits.daily_minutes.is_some_and(|m| summary.today.audio_secs >= m * 60.0) {
+            exceeded.push((summary.today.period.clone(), "daily_minutes"));
+        }
+        if limits.monthly_minutes.is_some_and(|m| summary.this_month.audio_secs >= m * 60.0) {
+            exceeded.push((summary.this_month.period.clone(), "monthly_minutes"));
+        }
+        if limits
+            .monthly_cost_usd
+            .is_some_and(|c| summary.this_month.estimated_cost_usd >= c)
+        {
+            exceeded.push((summary.this_month.period.clone(), "monthly_cost_usd"));
+        }
+        exceeded
+    }
+}
+
+fn describe(limit: &str) -> &'static str {
+    match limit {
+        "daily_minutes" => "daily cloud minutes",
+        "monthly_minutes" => "monthly cloud minutes",
+        _ => "monthly cloud spend",
+    }
+}
+
+/// Errors if a hard limit has been reached, so the caller can fall back to local Whisper.
+pub fn check_hard_limits(app: &AppHandle) -> Result<(), String> {
+    let limits = crate::AppSettings::get_or_default(app).usage_limits;
+    if limits.hard == LimitThresholds::default() {
+        return Ok(());
+    }
+
+    // Unreadable usage can't show that a limit hasn't been reached.
+    let log = UsageLog::load(app).map_err(|e| format!("Can't check usage limits: {}", e))?;
+    match log.exceeded(&limits.hard).first() {
+        Some((_, limit)) => Err(format!("Hard limit for {} reached", describe(limit))),
+        None => Ok(()),
+    }
+}
+
+/// Logs a completed cloud request and shows a one-off notification for any
+/// soft limit it pushed usage past.
+pub fn record(app: &AppHandle, record: UsageRecord) {
+    // Saving over a file we couldn't read would wipe the history in it.
+    let mut log = match UsageLog::load(app) {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!("Not recording usage, existing log is unreadable: {}", e);
+            return;
+        }
+    };
+    log.add_record(record);
+
+    let limits = crate::AppSettings::get_or_default(app).usage_limits;
+    for (period, limit) in log.exceeded(&limits.soft) {
+        if log.warned.insert(format!("{}:{}", limit, period)) {
+            println!("💸 Soft limit for {} reached ({})", describe(limit), period);
+            let _ = app
+                .notification()
+                .builder()
+                .title("Cloud usage limit reached")
+                .body(format!(
+                    "You've reached your soft limit for {}. Transcription will keep using the cloud.",
+                    describe(limit)
+                ))
+                .show();
+        }
+    }
+
+    if let Err(e) = log.save(app) {
+        eprintln!("Failed to save usage: {}", e);
+    }
+}
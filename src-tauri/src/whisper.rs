@@ -10,7 +10,8 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use tauri::AppHandle;
 use tauri_specta::Event;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use crate::transcripts::TranscriptSegment;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState};
 
 const WHISPER_SAMPLE_RATE: u32 = 16_000;
 
@@ -18,6 +19,10 @@ const WHISPER_SAMPLE_RATE: u32 = 16_000;
 pub struct WhisperRuntimeConfig {
     pub language: Option<String>,
     pub temperature: Option<f32>,
+    pub initial_prompt: Option<String>,
+    // Set by dictation profiles. Each backend only reads its own override.
+    pub local_model: Option<String>, // Whisper model ID used instead of the selected one
+    pub cloud_model: Option<String>, // Provider model name used instead of the configured one
 }
 
 impl Default for WhisperRuntimeConfig {
@@ -25,6 +30,9 @@ impl Default for WhisperRuntimeConfig {
         Self {
             language: Some("en".to_string()),
             temperature: Some(0.0),
+            initial_prompt: None,
+            local_model: None,
+            cloud_model: None,
         }
     }
 }
@@ -60,6 +68,17 @@ pub struct WhisperModelInfo {
     pub url: String,
     pub filename: String,
     pub recommended_for: Vec<String>,
+    #[serde(default)]
+    pub is_recommended: bool,
+    #[serde(default)]
+    pub supports_diarization: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptionOutput {
+    pub text: String,
+    pub segments: Vec<TranscriptSegment>,
+    pub language: Option<String>,
 }
 
 impl WhisperModelInfo {
@@ -73,6 +92,8 @@ impl WhisperModelInfo {
                 url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo-q8_0.bin".to_string(),
                 filename: "ggml-large-v3-turbo-q8_0.bin".to_string(),
                 recommended_for: vec!["accuracy".to_string(), "performance".to_string()],
+                is_recommended: false,
+                supports_diarization: false,
             },
             Self {
                 id: "large-v3-turbo-q5_0".to_string(),
@@ -82,6 +103,8 @@ impl WhisperModelInfo {
                 url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo-q5_0.bin".to_string(),
                 filename: "ggml-large-v3-turbo-q5_0.bin".to_string(),
                 recommended_for: vec!["slower_machines".to_string()],
+                is_recommended: false,
+                supports_diarization: false,
             },
             Self {
                 id: "distil-large-v3.5-q8_0".to_string(),
@@ -91,6 +114,19 @@ impl WhisperModelInfo {
                 url: "https://huggingface.co/distil-whisper/distil-large-v3.5-ggml/resolve/main/ggml-model.bin".to_string(),
                 filename: "ggml-model.bin".to_string(),
                 recommended_for: vec!["accuracy".to_string(), "speed".to_string()],
+                is_recommended: false,
+                supports_diarization: false,
+            },
+            Self {
+                id: "small.en-tdrz".to_string(),
+                name: "Small.en TinyDiarize".to_string(),
+                size_mb: 465,
+                description: "Marks speaker turns in conversations (English only)".to_string(),
+                url: "https://huggingface.co/akashmjn/tinydiarize-whisper.cpp/resolve/main/ggml-small.en-tdrz.bin".to_string(),
+                filename: "ggml-small.en-tdrz.bin".to_string(),
+                recommended_for: vec!["conversations".to_string()],
+                is_recommended: false,
+                supports_diarization: true,
             },
         ]
     }
@@ -101,8 +137,41 @@ impl WhisperModelInfo {
 }
 
 pub struct WhisperModel {
-    context: Option<Arc<WhisperContext>>,
+    contexts: HashMap<String, Arc<WhisperContext>>,
     current_model_id: Option<String>,
+    draft_model_id: Option<String>,
+    profile_model_id: Option<String>,
+}
+
+/// A handle to one loaded model that can be used without holding the `WhisperModel` lock.
+#[derive(Clone)]
+pub struct LoadedModel {
+    pub model_id: String,
+    context: Arc<WhisperContext>,
+}
+
+impl LoadedModel {
+    pub fn transcribe_with_config(
+        &self,
+        audio_data: &[f32],
+        sample_rate: u32,
+        config: &WhisperRuntimeConfig,
+    ) -> Result<String, String> {
+        self.transcribe_detailed(audio_data, sample_rate, config)
+            .map(|output| output.text)
+    }
+
+    pub fn transcribe_detailed(
+        &self,
+        audio_data: &[f32],
+        sample_rate: u32,
+        config: &WhisperRuntimeConfig,
+    ) -> Result<TranscriptionOutput, String> {
+        let diarize = WhisperModelInfo::get_by_id(&self.model_id)
+            .map(|info| info.supports_diarization)
+            .unwrap_or(false);
+        WhisperModel::transcribe_with_context(&self.context, audio_data, sample_rate, config, diarize)
+    }
 }
 
 impl Default for WhisperModel {
@@ -114,8 +183,10 @@ impl Default for WhisperModel {
 impl WhisperModel {
     pub fn new() -> Self {
         Self {
-            context: None,
+            contexts: HashMap::new(),
             current_model_id: None,
+            draft_model_id: None,
+            profile_model_id: None,
         }
     }
 
@@ -124,6 +195,52 @@ impl WhisperModel {
             .or_else(|| self.current_model_id.clone())
             .ok_or_else(|| "No model specified".to_string())?;
 
+        self.load_context(&model_id)?;
+        self.current_model_id = Some(model_id);
+        self.unload_unused();
+
+        println!("✅ Model loaded successfully");
+        Ok(())
+    }
+
+    /// Keeps a second, usually smaller, model resident for two-pass transcription.
+    /// Passing `None` unloads it.
+    pub fn load_draft_model(&mut self, model_id: Option<String>) -> Result<(), String> {
+        if let Some(id) = &model_id {
+            self.load_context(id)?;
+            println!("✅ Draft model loaded: {}", id);
+        }
+        self.draft_model_id = model_id;
+        self.unload_unused();
+        Ok(())
+    }
+
+    /// Loads the model a dictation profile asks for and keeps it resident next
+    /// to the selected one. Only the most recently used profile model is kept.
+    pub fn load_profile_model(&mut self, model_id: &str) -> Result<LoadedModel, String> {
+        let context = self.load_context(model_id)?;
+        self.profile_model_id = Some(model_id.to_string());
+        self.unload_unused();
+        Ok(LoadedModel {
+            model_id: model_id.to_string(),
+            context,
+        })
+    }
+
+    fn unload_unused(&mut self) {
+        let keep = [
+            self.current_model_id.clone(),
+            self.draft_model_id.clone(),
+            self.profile_model_id.clone(),
+        ];
+        self.contexts.retain(|id, _| keep.iter().any(|k| k.as_ref() == Some(id)));
+    }
+
+    fn load_context(&mut self, model_id: &str) -> Result<Arc<WhisperContext>, String> {
+        if let Some(ctx) = self.contexts.get(model_id) {
+            return Ok(ctx.clone());
+        }
+
         let model_info = WhisperModelInfo::get_by_id(&model_id)
             .ok_or_else(|| format!("Model not found: {}", model_id))?;
 
@@ -183,11 +300,31 @@ impl WhisperModel {
         let ctx = WhisperContext::new_with_params(model_path.to_str().unwrap(), params)
             .map_err(|e| format!("Failed to load model: {:?}", e))?;
 
-        self.context = Some(Arc::new(ctx));
-        self.current_model_id = Some(model_id);
+        let ctx = Arc::new(ctx);
+        self.contexts.insert(model_id.to_string(), ctx.clone());
+        Ok(ctx)
+    }
 
-        println!("✅ Model loaded successfully");
-        Ok(())
+    pub fn loaded(&self, model_id: &str) -> Option<LoadedModel> {
+        self.contexts.get(model_id).map(|context| LoadedModel {
+            model_id: model_id.to_string(),
+            context: context.clone(),
+        })
+    }
+
+    pub fn primary(&self) -> Option<LoadedModel> {
+        self.current_model_id.as_deref().and_then(|id| self.loaded(id))
+    }
+
+    pub fn draft(&self) -> Option<LoadedModel> {
+        self.draft_model_id.as_deref().and_then(|id| self.loaded(id))
+    }
+
+    fn current_context(&self) -> Result<&Arc<WhisperContext>, String> {
+        self.current_model_id
+            .as_ref()
+            .and_then(|id| self.contexts.get(id))
+            .ok_or_else(|| "Model not loaded".to_string())
     }
 
     pub fn transcribe(&self, audio_data: &[f32], sample_rate: u32) -> Result<String, String> {
@@ -200,12 +337,29 @@ impl WhisperModel {
         sample_rate: u32,
         config: &WhisperRuntimeConfig,
     ) -> Result<String, String> {
-        let start_time = std::time::Instant::now();
+        self.transcribe_detailed(audio_data, sample_rate, config)
+            .map(|output| output.text)
+    }
 
-        let context = self
-            .context
-            .as_ref()
-            .ok_or_else(|| "Model not loaded".to_string())?;
+    pub fn transcribe_detailed(
+        &self,
+        audio_data: &[f32],
+        sample_rate: u32,
+        config: &WhisperRuntimeConfig,
+    ) -> Result<TranscriptionOutput, String> {
+        self.primary()
+            .ok_or_else(|| "Model not loaded".to_string())?
+            .transcribe_detailed(audio_data, sample_rate, config)
+    }
+
+    fn transcribe_with_context(
+        context: &WhisperContext,
+        audio_data: &[f32],
+        sample_rate: u32,
+        config: &WhisperRuntimeConfig,
+        diarize: bool,
+    ) -> Result<TranscriptionOutput, String> {
+        let start_time = std::time::Instant::now();
 
         let resample_start = std::time::Instant::now();
         let resampled_audio = if sample_rate != WHISPER_SAMPLE_RATE {
@@ -231,8 +385,10 @@ impl WhisperModel {
         params.set_temperature_inc(0.0);
         params.set_temperature(config.temperature.unwrap_or(0.0));
 
-        params.set_single_segment(true);
-        params.set_no_timestamps(true);
+        // Speaker turns are only predicted at segment boundaries, which need timestamps.
+        params.set_single_segment(!diarize);
+        params.set_no_timestamps(!diarize);
+        params.set_tdrz_enable(diarize);
 
         params.set_max_initial_ts(0.0);
         params.set_max_len(0);
@@ -257,6 +413,16 @@ impl WhisperModel {
 
         params.set_no_context(true);
 
+        if let Some(prompt) = config
+            .initial_prompt
+            .as_ref()
+            .map(|p| p.replace('\0', ""))
+            .filter(|p| !p.trim().is_empty())
+        {
+            println!("🧩 Using carried-over context ({} chars)", prompt.chars().count());
+            params.set_initial_prompt(&prompt);
+        }
+
         let mut state = context
             .create_state()
             .map_err(|e| format!("Failed to create state: {:?}", e))?;
@@ -272,19 +438,74 @@ impl WhisperModel {
             .full_n_segments()
             .map_err(|e| format!("Failed to get segments: {:?}", e))?;
         let mut text = String::new();
+        let mut segments = Vec::new();
+        // tinydiarize only predicts that the speaker changes, not who is speaking,
+        // so turns alternate between two labels.
+        let mut speaker_turns = 0u32;
 
         for i in 0..num_segments {
             let segment = state
                 .full_get_segment_text(i)
                 .map_err(|e| format!("Failed to get segment text: {:?}", e))?;
+            let t0 = state
+                .full_get_segment_t0(i)
+                .map_err(|e| format!("Failed to get segment start: {:?}", e))?;
+            let t1 = state
+                .full_get_segment_t1(i)
+                .map_err(|e| format!("Failed to get segment end: {:?}", e))?;
+            let speaker_turn_next = diarize && state.full_get_segment_speaker_turn_next(i);
+            let avg_logprob = Self::segment_avg_logprob(context, &state, i);
+
             text.push_str(&segment);
+            segments.push(TranscriptSegment {
+                start_ms: t0 as f64 * 10.0,
+                end_ms: t1 as f64 * 10.0,
+                text: segment.trim().to_string(),
+                speaker: diarize.then_some(speaker_turns % 2 + 1),
+                speaker_turn_next,
+                avg_logprob,
+                no_speech_prob: None,
+            });
+
+            if speaker_turn_next {
+                speaker_turns += 1;
+            }
         }
         println!("⏱️ Extracting text took: {:?}", extract_start.elapsed());
 
+        if diarize {
+            println!("🗣️ Detected {} speaker turns", speaker_turns);
+        }
         println!("📝 Transcribed text: {:?}", text.trim());
         println!("⏱️ Total transcribe() took: {:?}", start_time.elapsed());
 
-        Ok(text.trim().to_string())
+        let language = state
+            .full_lang_id_from_state()
+            .ok()
+            .and_then(whisper_rs::get_lang_str)
+            .map(|lang| lang.to_string());
+
+        Ok(TranscriptionOutput {
+            text: text.trim().to_string(),
+            segments,
+            language,
+        })
+    }
+
+    /// Mean log probability of the segment's text tokens, comparable to the
+    /// `avg_logprob` reported by OpenAI-style APIs.
+    fn segment_avg_logprob(context: &WhisperContext, state: &WhisperState, segment: i32) -> Option<f32> {
+        let eot = context.token_eot();
+        let n_tokens = state.full_n_tokens(segment).ok()?;
+        let logprobs: Vec<f32> = (0..n_tokens)
+            .filter(|&t| state.full_get_token_id(segment, t).map(|id| id < eot).unwrap_or(false))
+            .filter_map(|t| state.full_get_token_prob(segment, t).ok())
+            .map(|p| p.max(f32::MIN_POSITIVE).ln())
+            .collect();
+        if logprobs.is_empty() {
+            return None;
+        }
+        Some(logprobs.iter().sum::<f32>() / logprobs.len() as f32)
     }
 
     fn resample_audio(input: &[f32], from_rate: u32, to_rate: u32) -> Result<Vec<f32>, String> {
@@ -415,7 +636,28 @@ impl WhisperModel {
             return Ok(());
         }
 
-        let client = reqwest::Client::new();
+        if let Err(e) = crate::net::check_egress(app_handle, "model download", &model_info.url) {
+            ModelDownloadComplete {
+                success: false,
+                error: Some(e.clone()),
+            }
+            .emit(app_handle)
+            .ok();
+            return Err(e);
+        }
+
+        let client = match crate::net::http_client(app_handle) {
+            Ok(client) => client,
+            Err(e) => {
+                ModelDownloadComplete {
+                    success: false,
+                    error: Some(e.clone()),
+                }
+                .emit(app_handle)
+                .ok();
+                return Err(e);
+            }
+        };
 
         let response = client
             .get(&model_info.url)
@@ -492,10 +734,7 @@ impl WhisperModel {
     where
         F: FnMut(&str, bool),
     {
-        let context = self
-            .context
-            .as_ref()
-            .ok_or_else(|| "Model not loaded".to_string())?;
+        let context = self.current_context()?;
 
         let resampled_audio = if sample_rate != WHISPER_SAMPLE_RATE {
             println!(
@@ -223,6 +223,38 @@ pub fn hide_bubble_window(app: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Resizes the bubble around its bottom centre, so it can grow to fit
+/// streamed text and shrink back without moving off its spot above the Dock.
+pub fn resize_bubble_window(app: &AppHandle, bubble_width: f64, bubble_height: f64) -> Result<(), String> {
+    let window = app
+        .get_webview_window("bubble")
+        .ok_or_else(|| "Bubble window not found".to_string())?;
+    let scale_factor = window.scale_factor().map_err(|e| e.to_string())?;
+    let position = window
+        .outer_position()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale_factor);
+    let size = window
+        .outer_size()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale_factor);
+
+    let horizontal_padding = 20.0;
+    let vertical_padding = 20.0;
+    let window_width = bubble_width + horizontal_padding;
+    let window_height = bubble_height + vertical_padding;
+
+    window
+        .set_size(LogicalSize::new(window_width, window_height))
+        .map_err(|e| e.to_string())?;
+    window
+        .set_position(LogicalPosition::new(
+            position.x + (size.width - window_width) / 2.0,
+            position.y + size.height - window_height,
+        ))
+        .map_err(|e| e.to_string())
+}
+
 #[cfg(target_os = "macos")]
 fn current_dock_height() -> f64 {
     use objc::runtime::Object;
@@ -275,22 +307,21 @@ pub fn start_dock_monitor(app: &AppHandle) {
         loop {
             let height = current_dock_height();
             if (height - previous_height).abs() > 1.0 {
-                if let Some(monitor) = app_handle.primary_monitor().unwrap_or(None) {
+                if let (Some(monitor), Some(window)) = (
+                    app_handle.primary_monitor().unwrap_or(None),
+                    app_handle.get_webview_window("bubble"),
+                ) {
                     let scale_factor = monitor.scale_factor();
                     let screen_size = monitor.size();
-                    let bubble_width = 70.0;
-                    let bubble_height = 35.0;
-                    let horizontal_padding = 20.0;
-                    let vertical_padding = 20.0;
-                    let window_width = bubble_width + horizontal_padding;
-                    let window_height = bubble_height + vertical_padding;
-                    let gap_above_dock = 5.0;
-                    let x = (screen_size.width as f64 / scale_factor - window_width) / 2.0;
-                    let y = screen_size.height as f64 / scale_factor
-                        - window_height
-                        - height
-                        - gap_above_dock;
-                    if let Some(window) = app_handle.get_webview_window("bubble") {
+                    // The bubble may be enlarged while a rewrite streams into it.
+                    if let Ok(size) = window.outer_size() {
+                        let size = size.to_logical::<f64>(scale_factor);
+                        let gap_above_dock = 5.0;
+                        let x = (screen_size.width as f64 / scale_factor - size.width) / 2.0;
+                        let y = screen_size.height as f64 / scale_factor
+                            - size.height
+                            - height
+                            - gap_above_dock;
                         let _ = window.set_position(LogicalPosition::new(x, y));
                     }
                 }
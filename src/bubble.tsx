@@ -1,5 +1,5 @@
 import { render } from "solid-js/web";
-import { createSignal, onMount, onCleanup, For } from "solid-js";
+import { createSignal, onMount, onCleanup, For, Show } from "solid-js";
 import { events } from "./bindings";
 import "./app.css";
 import "./bubble.css";
@@ -10,6 +10,8 @@ function BubbleApp() {
   );
   const [isAnimatingIn, setIsAnimatingIn] = createSignal(true);
   const [isAnimatingOut, setIsAnimatingOut] = createSignal(false);
+  // Text streamed back by an LLM rewrite or voice command, shown in place of the levels.
+  const [streamText, setStreamText] = createSignal<string | null>(null);
 
   let animationFrame: number | null = null;
   let levelDecayTimer: number | null = null;
@@ -24,6 +26,7 @@ function BubbleApp() {
     const recordingStateUnlisten = await events.recordingStateChanged.listen(
       (event) => {
         if (event.payload.is_recording) {
+          setStreamText(null);
           setIsAnimatingOut(false);
           setIsAnimatingIn(true);
           setTimeout(() => setIsAnimatingIn(false), 300);
@@ -34,11 +37,17 @@ function BubbleApp() {
       }
     );
 
+    const transcriptionProgressUnlisten =
+      await events.transcriptionProgress.listen((event) => {
+        setStreamText(event.payload.is_final ? null : event.payload.text);
+      });
+
     startSimulatedAudioLevels();
 
     onCleanup(() => {
       audioLevelUnlisten();
       recordingStateUnlisten();
+      transcriptionProgressUnlisten();
       if (animationFrame) cancelAnimationFrame(animationFrame);
       if (levelDecayTimer) clearInterval(levelDecayTimer);
     });
@@ -68,27 +77,41 @@ function BubbleApp() {
   };
 
   return (
-    <div
-      class={`flex items-center justify-center bg-black/90 rounded-[17.5px] shadow shadow-black/25 origin-bottom transition-all duration-300 ease-[cubic-bezier(0.34,1.56,0.64,1)] ${
-        isAnimatingIn() || isAnimatingOut()
-          ? "w-10 h-[10px] opacity-0 scale-[0.8] translate-y-[10px]"
-          : "w-[70px] h-[35px] opacity-100 scale-100"
-      }`}
+    <Show
+      when={streamText()}
+      fallback={
+        <div
+          class={`flex items-center justify-center bg-black/90 rounded-[17.5px] shadow shadow-black/25 origin-bottom transition-all duration-300 ease-[cubic-bezier(0.34,1.56,0.64,1)] ${
+            isAnimatingIn() || isAnimatingOut()
+              ? "w-10 h-[10px] opacity-0 scale-[0.8] translate-y-[10px]"
+              : "w-[70px] h-[35px] opacity-100 scale-100"
+          }`}
+        >
+          <div class="flex items-center gap-[2px] h-full px-[10px]">
+            <For each={audioLevels()}>
+              {(level) => (
+                <div
+                  class="w-[2px] bg-white rounded transition-all duration-100 ease-out"
+                  style={{
+                    height: `${10 + level * 15}px`,
+                    opacity: 0.6 + level * 0.4,
+                  }}
+                />
+              )}
+            </For>
+          </div>
+        </div>
+      }
     >
-      <div class="flex items-center gap-[2px] h-full px-[10px]">
-        <For each={audioLevels()}>
-          {(level) => (
-            <div
-              class="w-[2px] bg-white rounded transition-all duration-100 ease-out"
-              style={{
-                height: `${10 + level * 15}px`,
-                opacity: 0.6 + level * 0.4,
-              }}
-            />
-          )}
-        </For>
-      </div>
-    </div>
+      {(text) => (
+        <div class="flex flex-col justify-end w-[340px] h-[70px] px-3 py-2 bg-black/90 rounded-[17.5px] shadow shadow-black/25 overflow-hidden">
+          {/* Bottom-aligned so the newest text stays in view as it streams in. */}
+          <p class="text-white text-xs leading-4 whitespace-pre-wrap break-words">
+            {text()}
+          </p>
+        </div>
+      )}
+    </Show>
   );
 }
 